@@ -3,8 +3,10 @@
 
 /* auto-generated by NAPI-RS */
 
-export declare function normalizeJson(payloadJson: string): string
-export declare function deterministicHash(payloadJson: string): string
+export declare function normalizeJson(payloadJson: string, optionsJson?: string | undefined | null): string
+export declare function deterministicHash(payloadJson: string, optionsJson?: string | undefined | null): string
+export declare function hashSchemes(): Array<string>
+export declare function defaultHashScheme(): string
 export interface NormalizeAndHashResult {
   normalizedJson: string
   deterministicHash: string
@@ -13,9 +15,20 @@ export interface ProcessExportPayloadResult {
   normalizedJson: string
   decodedJson: string
   decodedValueHash: string
+  matchHash?: string
+  importanceHash: string
+  fingerprint?: string
+  pathHashes: Record<string, string>
   decodedSchemaJson: string
   decodedSchemaHash: string
+  shapeHash: string
+  hashScheme?: string
   protobufStructBytes: Buffer
+  redactions: Array<DetectedRedaction>
+}
+export interface DetectedRedaction {
+  path: string
+  detector: string
 }
 export interface BuildSpanProtoBytesInput {
   traceId: string
@@ -48,9 +61,42 @@ export interface BuildSpanProtoBytesInput {
   inputValueProtoStructBytes?: Buffer
   outputValueProtoStructBytes?: Buffer
 }
-export declare function normalizeAndHash(payloadJson: string): NormalizeAndHashResult
+export declare function normalizeAndHash(payloadJson: string, optionsJson?: string | undefined | null): NormalizeAndHashResult
 export declare function objectToProtobufStructBytes(payloadJson: string): Buffer
 export declare function objectToProtobufStructFieldCount(payloadJson: string): number
-export declare function processExportPayload(payloadJson: string, schemaMergesJson?: string | undefined | null): ProcessExportPayloadResult
+export declare function processExportPayload(payloadJson: string, schemaMergesJson?: string | undefined | null, optionsJson?: string | undefined | null): ProcessExportPayloadResult
+export interface NormalizeAndHashBatchItem {
+  result?: NormalizeAndHashResult
+  error?: string
+}
+export declare function normalizeAndHashBatch(payloadsJson: Array<string>, optionsJson?: string | undefined | null): Promise<Array<NormalizeAndHashBatchItem>>
+export interface ProcessExportPayloadBatchItem {
+  result?: ProcessExportPayloadResult
+  error?: string
+}
+export declare function processExportPayloadBatch(payloadsJson: Array<string>, schemaMergesJson?: Array<string | undefined | null> | undefined | null, optionsJson?: string | undefined | null): Promise<Array<ProcessExportPayloadBatchItem>>
+export declare function accumulatePayloadSchemas(payloadsJson: Array<string>, stateJson?: string | undefined | null, schemaMergesJson?: Array<string | undefined | null> | undefined | null, optionsJson?: string | undefined | null): string
+export declare function accumulateSchemas(schemasJson: Array<string>, stateJson?: string | undefined | null): string
+export declare function mergeSchemaAccumulators(statesJson: Array<string>): string
+export declare function driftSchemaToJsonSchema(schemaJson: string): string
+export declare function jsonSchemaToDriftSchema(jsonSchema: string): string
+export declare function driftSchemaProtoToJsonSchema(schemaProtoBytes: Buffer): string
+export declare function jsonSchemaToDriftSchemaProto(jsonSchema: string): Buffer
+export interface SchemaChange {
+  path: string
+  kind: string
+  breaking: boolean
+  beforeJson?: string
+  afterJson?: string
+}
+export declare function diffSchemas(oldSchemaJson: string, newSchemaJson: string): Array<SchemaChange>
+export interface SchemaViolation {
+  path: string
+  expected: Array<number>
+  actual: number
+}
+export declare function validateAgainstSchema(valueJson: string, schemaJson: string): Array<SchemaViolation>
+export declare function validateAgainstSchemaProto(valueJson: string, schemaProtoBytes: Buffer): Array<SchemaViolation>
+export declare function fingerprintSimilarity(a: string, b: string): number
 export declare function buildSpanProtoBytes(input: BuildSpanProtoBytesInput): Buffer
 export declare function buildExportSpansRequestBytes(observableServiceId: string, environment: string, sdkVersion: string, sdkInstanceId: string, spans: Array<Buffer>): Buffer
@@ -310,13 +310,28 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { normalizeJson, deterministicHash, normalizeAndHash, objectToProtobufStructBytes, objectToProtobufStructFieldCount, processExportPayload, buildSpanProtoBytes, buildExportSpansRequestBytes } = nativeBinding
+const { normalizeJson, deterministicHash, hashSchemes, defaultHashScheme, normalizeAndHash, objectToProtobufStructBytes, objectToProtobufStructFieldCount, processExportPayload, normalizeAndHashBatch, processExportPayloadBatch, accumulatePayloadSchemas, accumulateSchemas, mergeSchemaAccumulators, driftSchemaToJsonSchema, jsonSchemaToDriftSchema, driftSchemaProtoToJsonSchema, jsonSchemaToDriftSchemaProto, diffSchemas, validateAgainstSchema, validateAgainstSchemaProto, fingerprintSimilarity, buildSpanProtoBytes, buildExportSpansRequestBytes } = nativeBinding
 
 module.exports.normalizeJson = normalizeJson
 module.exports.deterministicHash = deterministicHash
+module.exports.hashSchemes = hashSchemes
+module.exports.defaultHashScheme = defaultHashScheme
 module.exports.normalizeAndHash = normalizeAndHash
 module.exports.objectToProtobufStructBytes = objectToProtobufStructBytes
 module.exports.objectToProtobufStructFieldCount = objectToProtobufStructFieldCount
 module.exports.processExportPayload = processExportPayload
+module.exports.normalizeAndHashBatch = normalizeAndHashBatch
+module.exports.processExportPayloadBatch = processExportPayloadBatch
+module.exports.accumulatePayloadSchemas = accumulatePayloadSchemas
+module.exports.accumulateSchemas = accumulateSchemas
+module.exports.mergeSchemaAccumulators = mergeSchemaAccumulators
+module.exports.driftSchemaToJsonSchema = driftSchemaToJsonSchema
+module.exports.jsonSchemaToDriftSchema = jsonSchemaToDriftSchema
+module.exports.driftSchemaProtoToJsonSchema = driftSchemaProtoToJsonSchema
+module.exports.jsonSchemaToDriftSchemaProto = jsonSchemaToDriftSchemaProto
+module.exports.diffSchemas = diffSchemas
+module.exports.validateAgainstSchema = validateAgainstSchema
+module.exports.validateAgainstSchemaProto = validateAgainstSchemaProto
+module.exports.fingerprintSimilarity = fingerprintSimilarity
 module.exports.buildSpanProtoBytes = buildSpanProtoBytes
 module.exports.buildExportSpansRequestBytes = buildExportSpansRequestBytes
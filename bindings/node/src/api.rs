@@ -1,19 +1,64 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use tusk_drift_schemas::tusk::drift::core::v1::{PackageType, SpanKind, StatusCode};
 
-use crate::conversion::{tuple_to_normalize_and_hash_result, vec_to_buffer};
+use crate::conversion::{
+    export_payload_result_to_napi, schema_change_to_napi, schema_violation_to_napi,
+    tuple_to_normalize_and_hash_result, vec_to_buffer,
+};
 use crate::error::map_core_err;
 
+fn normalize_options(options_json: Option<&str>) -> Result<drift_rust_core::NormalizeOptions> {
+    options_json
+        .map(drift_rust_core::NormalizeOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
+fn hash_options(options_json: Option<&str>) -> Result<drift_rust_core::HashOptions> {
+    options_json
+        .map(drift_rust_core::HashOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
+fn export_payload_options(
+    options_json: Option<&str>,
+) -> Result<drift_rust_core::ExportPayloadOptions> {
+    options_json
+        .map(drift_rust_core::ExportPayloadOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
 #[napi]
-pub fn normalize_json(payload_json: String) -> Result<String> {
-    drift_rust_core::normalize_json(&payload_json).map_err(map_core_err)
+pub fn normalize_json(payload_json: String, options_json: Option<String>) -> Result<String> {
+    let options = normalize_options(options_json.as_deref())?;
+    drift_rust_core::normalize_json_with_options(&payload_json, &options).map_err(map_core_err)
 }
 
 #[napi]
-pub fn deterministic_hash(payload_json: String) -> Result<String> {
-    drift_rust_core::deterministic_hash(&payload_json).map_err(map_core_err)
+pub fn deterministic_hash(payload_json: String, options_json: Option<String>) -> Result<String> {
+    let options = hash_options(options_json.as_deref())?;
+    drift_rust_core::deterministic_hash_with_options(&payload_json, &options).map_err(map_core_err)
+}
+
+#[napi]
+pub fn hash_schemes() -> Vec<String> {
+    drift_rust_core::HashScheme::ALL
+        .iter()
+        .map(|scheme| scheme.as_str().to_string())
+        .collect()
+}
+
+#[napi]
+pub fn default_hash_scheme() -> String {
+    drift_rust_core::HashScheme::default().as_str().to_string()
 }
 
 #[napi(object)]
@@ -27,9 +72,22 @@ pub struct ProcessExportPayloadResult {
     pub normalized_json: String,
     pub decoded_json: String,
     pub decoded_value_hash: String,
+    pub match_hash: Option<String>,
+    pub importance_hash: String,
+    pub fingerprint: Option<String>,
+    pub path_hashes: HashMap<String, String>,
     pub decoded_schema_json: String,
     pub decoded_schema_hash: String,
+    pub shape_hash: String,
+    pub hash_scheme: Option<String>,
     pub protobuf_struct_bytes: Buffer,
+    pub redactions: Vec<DetectedRedaction>,
+}
+
+#[napi(object)]
+pub struct DetectedRedaction {
+    pub path: String,
+    pub detector: String,
 }
 
 #[napi(object)]
@@ -66,8 +124,12 @@ pub struct BuildSpanProtoBytesInput {
 }
 
 #[napi]
-pub fn normalize_and_hash(payload_json: String) -> Result<NormalizeAndHashResult> {
-    drift_rust_core::normalize_and_hash(&payload_json)
+pub fn normalize_and_hash(
+    payload_json: String,
+    options_json: Option<String>,
+) -> Result<NormalizeAndHashResult> {
+    let options = hash_options(options_json.as_deref())?;
+    drift_rust_core::normalize_and_hash_with_options(&payload_json, &options)
         .map(tuple_to_normalize_and_hash_result)
         .map_err(map_core_err)
 }
@@ -90,19 +152,271 @@ pub fn object_to_protobuf_struct_field_count(payload_json: String) -> Result<u32
 pub fn process_export_payload(
     payload_json: String,
     schema_merges_json: Option<String>,
+    options_json: Option<String>,
 ) -> Result<ProcessExportPayloadResult> {
-    drift_rust_core::process_export_payload(&payload_json, schema_merges_json.as_deref())
-        .map(|result| ProcessExportPayloadResult {
-            normalized_json: result.normalized_json,
-            decoded_json: result.decoded_json,
-            decoded_value_hash: result.decoded_value_hash,
-            decoded_schema_json: result.decoded_schema_json,
-            decoded_schema_hash: result.decoded_schema_hash,
-            protobuf_struct_bytes: Buffer::from(result.protobuf_struct_bytes),
+    let options = export_payload_options(options_json.as_deref())?;
+    drift_rust_core::process_export_payload_with_options(
+        &payload_json,
+        schema_merges_json.as_deref(),
+        &options,
+    )
+    .map(export_payload_result_to_napi)
+    .map_err(map_core_err)
+}
+
+#[napi(object)]
+pub struct NormalizeAndHashBatchItem {
+    pub result: Option<NormalizeAndHashResult>,
+    pub error: Option<String>,
+}
+
+fn normalize_and_hash_batch_item(
+    result: drift_rust_core::CoreResult<(String, String)>,
+) -> NormalizeAndHashBatchItem {
+    match result {
+        Ok(tuple) => NormalizeAndHashBatchItem {
+            result: Some(tuple_to_normalize_and_hash_result(tuple)),
+            error: None,
+        },
+        Err(e) => NormalizeAndHashBatchItem {
+            result: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Hashes a batch on the libuv thread pool (and rayon inside the core), so
+/// the event loop keeps running.
+pub struct NormalizeAndHashBatchTask {
+    payloads_json: Vec<String>,
+    options_json: Option<String>,
+}
+
+impl Task for NormalizeAndHashBatchTask {
+    type Output = Vec<drift_rust_core::CoreResult<(String, String)>>;
+    type JsValue = Vec<NormalizeAndHashBatchItem>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let options = hash_options(self.options_json.as_deref())?;
+        let payloads: Vec<&str> = self.payloads_json.iter().map(String::as_str).collect();
+        Ok(drift_rust_core::normalize_and_hash_batch(
+            &payloads, &options,
+        ))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output
+            .into_iter()
+            .map(normalize_and_hash_batch_item)
+            .collect())
+    }
+}
+
+#[napi(ts_return_type = "Promise<Array<NormalizeAndHashBatchItem>>")]
+pub fn normalize_and_hash_batch(
+    payloads_json: Vec<String>,
+    options_json: Option<String>,
+) -> AsyncTask<NormalizeAndHashBatchTask> {
+    AsyncTask::new(NormalizeAndHashBatchTask {
+        payloads_json,
+        options_json,
+    })
+}
+
+#[napi(object)]
+pub struct ProcessExportPayloadBatchItem {
+    pub result: Option<ProcessExportPayloadResult>,
+    pub error: Option<String>,
+}
+
+/// One schema merges entry per payload; `None` means no merges for any.
+fn schema_merges_per_payload(
+    payload_count: usize,
+    schema_merges_json: Option<Vec<Option<String>>>,
+) -> Result<Vec<Option<String>>> {
+    let merges = schema_merges_json.unwrap_or_else(|| vec![None; payload_count]);
+    if merges.len() != payload_count {
+        return Err(Error::from_reason(format!(
+            "expected {} schema merges, got {}",
+            payload_count,
+            merges.len()
+        )));
+    }
+    Ok(merges)
+}
+
+fn process_export_payload_batch_item(
+    result: drift_rust_core::CoreResult<drift_rust_core::ExportPayloadResult>,
+) -> ProcessExportPayloadBatchItem {
+    match result {
+        Ok(result) => ProcessExportPayloadBatchItem {
+            result: Some(export_payload_result_to_napi(result)),
+            error: None,
+        },
+        Err(e) => ProcessExportPayloadBatchItem {
+            result: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Processes a batch off the event loop, like [`NormalizeAndHashBatchTask`].
+pub struct ProcessExportPayloadBatchTask {
+    payloads_json: Vec<String>,
+    schema_merges_json: Option<Vec<Option<String>>>,
+    options_json: Option<String>,
+}
+
+impl Task for ProcessExportPayloadBatchTask {
+    type Output = Vec<drift_rust_core::CoreResult<drift_rust_core::ExportPayloadResult>>;
+    type JsValue = Vec<ProcessExportPayloadBatchItem>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let options = export_payload_options(self.options_json.as_deref())?;
+        let merges =
+            schema_merges_per_payload(self.payloads_json.len(), self.schema_merges_json.take())?;
+        let items: Vec<(&str, Option<&str>)> = self
+            .payloads_json
+            .iter()
+            .zip(&merges)
+            .map(|(payload, merges)| (payload.as_str(), merges.as_deref()))
+            .collect();
+        Ok(drift_rust_core::process_export_payload_batch(
+            &items, &options,
+        ))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output
+            .into_iter()
+            .map(process_export_payload_batch_item)
+            .collect())
+    }
+}
+
+#[napi(ts_return_type = "Promise<Array<ProcessExportPayloadBatchItem>>")]
+pub fn process_export_payload_batch(
+    payloads_json: Vec<String>,
+    schema_merges_json: Option<Vec<Option<String>>>,
+    options_json: Option<String>,
+) -> AsyncTask<ProcessExportPayloadBatchTask> {
+    AsyncTask::new(ProcessExportPayloadBatchTask {
+        payloads_json,
+        schema_merges_json,
+        options_json,
+    })
+}
+
+#[napi]
+pub fn accumulate_payload_schemas(
+    payloads_json: Vec<String>,
+    state_json: Option<String>,
+    schema_merges_json: Option<Vec<Option<String>>>,
+    options_json: Option<String>,
+) -> Result<String> {
+    let options = export_payload_options(options_json.as_deref())?;
+    let merges = schema_merges_per_payload(payloads_json.len(), schema_merges_json)?;
+    let items: Vec<(&str, Option<&str>)> = payloads_json
+        .iter()
+        .zip(&merges)
+        .map(|(payload, merges)| (payload.as_str(), merges.as_deref()))
+        .collect();
+    drift_rust_core::accumulate_payload_schemas(state_json.as_deref(), &items, &options)
+        .map_err(map_core_err)
+}
+
+#[napi]
+pub fn accumulate_schemas(schemas_json: Vec<String>, state_json: Option<String>) -> Result<String> {
+    let schemas: Vec<&str> = schemas_json.iter().map(String::as_str).collect();
+    drift_rust_core::accumulate_schemas(state_json.as_deref(), &schemas).map_err(map_core_err)
+}
+
+#[napi]
+pub fn merge_schema_accumulators(states_json: Vec<String>) -> Result<String> {
+    let states: Vec<&str> = states_json.iter().map(String::as_str).collect();
+    drift_rust_core::merge_schema_accumulators(&states).map_err(map_core_err)
+}
+
+#[napi]
+pub fn drift_schema_to_json_schema(schema_json: String) -> Result<String> {
+    drift_rust_core::drift_schema_to_json_schema(&schema_json).map_err(map_core_err)
+}
+
+#[napi]
+pub fn json_schema_to_drift_schema(json_schema: String) -> Result<String> {
+    drift_rust_core::json_schema_to_drift_schema(&json_schema).map_err(map_core_err)
+}
+
+#[napi]
+pub fn drift_schema_proto_to_json_schema(schema_proto_bytes: Buffer) -> Result<String> {
+    drift_rust_core::drift_schema_proto_to_json_schema(&schema_proto_bytes).map_err(map_core_err)
+}
+
+#[napi]
+pub fn json_schema_to_drift_schema_proto(json_schema: String) -> Result<Buffer> {
+    drift_rust_core::json_schema_to_drift_schema_proto(&json_schema)
+        .map(vec_to_buffer)
+        .map_err(map_core_err)
+}
+
+#[napi(object)]
+pub struct SchemaChange {
+    pub path: String,
+    pub kind: String,
+    pub breaking: bool,
+    pub before_json: Option<String>,
+    pub after_json: Option<String>,
+}
+
+#[napi]
+pub fn diff_schemas(old_schema_json: String, new_schema_json: String) -> Result<Vec<SchemaChange>> {
+    drift_rust_core::diff_schemas(&old_schema_json, &new_schema_json)
+        .map(|changes| changes.into_iter().map(schema_change_to_napi).collect())
+        .map_err(map_core_err)
+}
+
+#[napi(object)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub expected: Vec<i32>,
+    pub actual: i32,
+}
+
+#[napi]
+pub fn validate_against_schema(
+    value_json: String,
+    schema_json: String,
+) -> Result<Vec<SchemaViolation>> {
+    drift_rust_core::validate_against_schema(&value_json, &schema_json)
+        .map(|violations| {
+            violations
+                .into_iter()
+                .map(schema_violation_to_napi)
+                .collect()
+        })
+        .map_err(map_core_err)
+}
+
+#[napi]
+pub fn validate_against_schema_proto(
+    value_json: String,
+    schema_proto_bytes: Buffer,
+) -> Result<Vec<SchemaViolation>> {
+    drift_rust_core::validate_against_schema_proto(&value_json, &schema_proto_bytes)
+        .map(|violations| {
+            violations
+                .into_iter()
+                .map(schema_violation_to_napi)
+                .collect()
         })
         .map_err(map_core_err)
 }
 
+#[napi]
+pub fn fingerprint_similarity(a: String, b: String) -> Result<f64> {
+    drift_rust_core::fingerprint_similarity(&a, &b).map_err(map_core_err)
+}
+
 #[napi]
 pub fn build_span_proto_bytes(input: BuildSpanProtoBytesInput) -> Result<Buffer> {
     let input_schema: JsonValue = serde_json::from_str(&input.input_schema_json)
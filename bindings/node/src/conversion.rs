@@ -1,6 +1,9 @@
 use napi::bindgen_prelude::Buffer;
 
-use crate::api::NormalizeAndHashResult;
+use crate::api::{
+    DetectedRedaction, NormalizeAndHashResult, ProcessExportPayloadResult, SchemaChange,
+    SchemaViolation,
+};
 
 pub fn tuple_to_normalize_and_hash_result(tuple: (String, String)) -> NormalizeAndHashResult {
     NormalizeAndHashResult {
@@ -12,3 +15,48 @@ pub fn tuple_to_normalize_and_hash_result(tuple: (String, String)) -> NormalizeA
 pub fn vec_to_buffer(bytes: Vec<u8>) -> Buffer {
     Buffer::from(bytes)
 }
+
+pub fn export_payload_result_to_napi(
+    result: drift_rust_core::ExportPayloadResult,
+) -> ProcessExportPayloadResult {
+    ProcessExportPayloadResult {
+        normalized_json: result.normalized_json,
+        decoded_json: result.decoded_json,
+        decoded_value_hash: result.decoded_value_hash,
+        match_hash: result.match_hash,
+        importance_hash: result.importance_hash,
+        fingerprint: result.fingerprint,
+        path_hashes: result.path_hashes.into_iter().collect(),
+        decoded_schema_json: result.decoded_schema_json,
+        decoded_schema_hash: result.decoded_schema_hash,
+        shape_hash: result.shape_hash,
+        hash_scheme: result.hash_scheme.map(|scheme| scheme.as_str().to_string()),
+        protobuf_struct_bytes: vec_to_buffer(result.protobuf_struct_bytes),
+        redactions: result
+            .redactions
+            .into_iter()
+            .map(|r| DetectedRedaction {
+                path: r.path,
+                detector: r.detector.as_str().to_string(),
+            })
+            .collect(),
+    }
+}
+
+pub fn schema_change_to_napi(change: drift_rust_core::SchemaChange) -> SchemaChange {
+    SchemaChange {
+        path: change.path,
+        kind: change.kind.as_str().to_string(),
+        breaking: change.breaking,
+        before_json: change.before.map(|v| v.to_string()),
+        after_json: change.after.map(|v| v.to_string()),
+    }
+}
+
+pub fn schema_violation_to_napi(violation: drift_rust_core::SchemaViolation) -> SchemaViolation {
+    SchemaViolation {
+        path: violation.path,
+        expected: violation.expected,
+        actual: violation.actual,
+    }
+}
@@ -3,8 +3,13 @@ mod conversion;
 mod error;
 
 pub use api::{
-    BuildSpanProtoBytesInput, NormalizeAndHashResult, ProcessExportPayloadResult,
-    build_export_spans_request_bytes, build_span_proto_bytes, deterministic_hash,
-    normalize_and_hash, normalize_json, object_to_protobuf_struct_bytes,
-    object_to_protobuf_struct_field_count, process_export_payload,
+    BuildSpanProtoBytesInput, NormalizeAndHashResult, ProcessExportPayloadResult, SchemaChange,
+    SchemaViolation, accumulate_payload_schemas, accumulate_schemas,
+    build_export_spans_request_bytes, build_span_proto_bytes, default_hash_scheme,
+    deterministic_hash, diff_schemas, drift_schema_proto_to_json_schema,
+    drift_schema_to_json_schema, fingerprint_similarity, hash_schemes, json_schema_to_drift_schema,
+    json_schema_to_drift_schema_proto, merge_schema_accumulators, normalize_and_hash,
+    normalize_and_hash_batch, normalize_json, object_to_protobuf_struct_bytes,
+    object_to_protobuf_struct_field_count, process_export_payload, process_export_payload_batch,
+    validate_against_schema, validate_against_schema_proto,
 };
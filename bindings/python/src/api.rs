@@ -1,25 +1,64 @@
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyList};
+use pyo3::types::{PyBytes, PyDict, PyList};
 use tusk_drift_schemas::tusk::drift::core::v1::{PackageType, SpanKind, StatusCode};
 
 use crate::conversion::{
-    json_value_to_py, py_any_to_optional_bytes, py_any_to_optional_json, py_to_json_value,
+    json_value_to_py, py_any_to_optional_bytes, py_any_to_optional_json,
+    py_any_to_optional_json_string, py_to_json_value,
 };
 use crate::error::map_core_err;
 
+fn normalize_options(options_json: Option<&str>) -> PyResult<drift_rust_core::NormalizeOptions> {
+    options_json
+        .map(drift_rust_core::NormalizeOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
+fn hash_options(options_json: Option<&str>) -> PyResult<drift_rust_core::HashOptions> {
+    options_json
+        .map(drift_rust_core::HashOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
+#[pyfunction]
+#[pyo3(signature = (payload_json, options_json=None))]
+pub fn normalize_json(payload_json: &str, options_json: Option<&str>) -> PyResult<String> {
+    let options = normalize_options(options_json)?;
+    drift_rust_core::normalize_json_with_options(payload_json, &options).map_err(map_core_err)
+}
+
+#[pyfunction]
+#[pyo3(signature = (payload_json, options_json=None))]
+pub fn deterministic_hash(payload_json: &str, options_json: Option<&str>) -> PyResult<String> {
+    let options = hash_options(options_json)?;
+    drift_rust_core::deterministic_hash_with_options(payload_json, &options).map_err(map_core_err)
+}
+
 #[pyfunction]
-pub fn normalize_json(payload_json: &str) -> PyResult<String> {
-    drift_rust_core::normalize_json(payload_json).map_err(map_core_err)
+#[pyo3(signature = (payload_json, options_json=None))]
+pub fn normalize_and_hash(
+    payload_json: &str,
+    options_json: Option<&str>,
+) -> PyResult<(String, String)> {
+    let options = hash_options(options_json)?;
+    drift_rust_core::normalize_and_hash_with_options(payload_json, &options).map_err(map_core_err)
 }
 
 #[pyfunction]
-pub fn deterministic_hash(payload_json: &str) -> PyResult<String> {
-    drift_rust_core::deterministic_hash(payload_json).map_err(map_core_err)
+pub fn hash_schemes() -> Vec<&'static str> {
+    drift_rust_core::HashScheme::ALL
+        .iter()
+        .map(|scheme| scheme.as_str())
+        .collect()
 }
 
 #[pyfunction]
-pub fn normalize_and_hash(payload_json: &str) -> PyResult<(String, String)> {
-    drift_rust_core::normalize_and_hash(payload_json).map_err(map_core_err)
+pub fn default_hash_scheme() -> &'static str {
+    drift_rust_core::HashScheme::default().as_str()
 }
 
 #[pyfunction]
@@ -34,59 +73,346 @@ pub fn object_to_protobuf_struct_field_count(payload_json: &str) -> PyResult<u32
         .map_err(map_core_err)
 }
 
+fn export_payload_options(
+    options_json: Option<&str>,
+) -> PyResult<drift_rust_core::ExportPayloadOptions> {
+    options_json
+        .map(drift_rust_core::ExportPayloadOptions::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(map_core_err)
+}
+
+/// Whether Python numbers in a payload keep their exact digits; otherwise
+/// they convert the way they always have, so default hashes do not move.
+fn exact_numbers(options: &drift_rust_core::ExportPayloadOptions) -> bool {
+    options.normalize.lossless_numbers
+        || options.hash_scheme == Some(drift_rust_core::HashScheme::V2)
+}
+
+/// Returns `(normalized_json, decoded_json, decoded_value_hash,
+/// decoded_schema_json, decoded_schema_hash, protobuf_struct_bytes)`. The
+/// tuple is frozen for existing callers; the hashes and reports added since
+/// (`match_hash`, `importance_hash`, `fingerprint`, `path_hashes`,
+/// `shape_hash`, `hash_scheme`, `redactions`) are only in
+/// `process_export_payload_detailed`, which takes the payload as a Python
+/// object.
 #[pyfunction]
+#[pyo3(signature = (payload_json, schema_merges_json=None, options_json=None))]
 pub fn process_export_payload(
     payload_json: &str,
     schema_merges_json: Option<&str>,
-) -> PyResult<(String, String, String, String, String, Vec<u8>)> {
-    drift_rust_core::process_export_payload(payload_json, schema_merges_json)
-        .map(|result| {
-            (
-                result.normalized_json,
-                result.decoded_json,
-                result.decoded_value_hash,
-                result.decoded_schema_json,
-                result.decoded_schema_hash,
-                result.protobuf_struct_bytes,
-            )
-        })
+    options_json: Option<&str>,
+) -> PyResult<ExportPayloadTuple> {
+    let options = export_payload_options(options_json)?;
+    drift_rust_core::process_export_payload_with_options(payload_json, schema_merges_json, &options)
+        .map(export_payload_tuple)
         .map_err(map_core_err)
 }
 
+/// The legacy result shape of `process_export_payload`, kept as is; new result
+/// fields go to `process_export_payload_detailed`.
+type ExportPayloadTuple = (String, String, String, String, String, Vec<u8>);
+
+fn export_payload_tuple(result: drift_rust_core::ExportPayloadResult) -> ExportPayloadTuple {
+    (
+        result.normalized_json,
+        result.decoded_json,
+        result.decoded_value_hash,
+        result.decoded_schema_json,
+        result.decoded_schema_hash,
+        result.protobuf_struct_bytes,
+    )
+}
+
+/// One `(result, error)` pair per payload, in input order; exactly one side
+/// is `None`.
+fn batch_item<T>(result: drift_rust_core::CoreResult<T>) -> (Option<T>, Option<String>) {
+    match result {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e.to_string())),
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (payloads_json, options_json=None))]
+#[allow(clippy::type_complexity)]
+pub fn normalize_and_hash_batch(
+    py: Python<'_>,
+    payloads_json: Vec<String>,
+    options_json: Option<&str>,
+) -> PyResult<Vec<(Option<(String, String)>, Option<String>)>> {
+    let options = hash_options(options_json)?;
+    let payloads: Vec<&str> = payloads_json.iter().map(String::as_str).collect();
+    Ok(py.detach(|| {
+        drift_rust_core::normalize_and_hash_batch(&payloads, &options)
+            .into_iter()
+            .map(batch_item)
+            .collect()
+    }))
+}
+
+/// One schema merges entry per payload; `None` means no merges for any.
+fn schema_merges_per_payload(
+    payload_count: usize,
+    schema_merges_json: Option<Vec<Option<String>>>,
+) -> PyResult<Vec<Option<String>>> {
+    let merges = schema_merges_json.unwrap_or_else(|| vec![None; payload_count]);
+    if merges.len() != payload_count {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "expected {} schema merges, got {}",
+            payload_count,
+            merges.len()
+        )));
+    }
+    Ok(merges)
+}
+
+/// `process_export_payload` over many payloads, returning the same frozen
+/// tuple per item.
 #[pyfunction]
+#[pyo3(signature = (payloads_json, schema_merges_json=None, options_json=None))]
+#[allow(clippy::type_complexity)]
+pub fn process_export_payload_batch(
+    py: Python<'_>,
+    payloads_json: Vec<String>,
+    schema_merges_json: Option<Vec<Option<String>>>,
+    options_json: Option<&str>,
+) -> PyResult<Vec<(Option<ExportPayloadTuple>, Option<String>)>> {
+    let options = export_payload_options(options_json)?;
+    let merges = schema_merges_per_payload(payloads_json.len(), schema_merges_json)?;
+    let items: Vec<(&str, Option<&str>)> = payloads_json
+        .iter()
+        .zip(&merges)
+        .map(|(payload, merges)| (payload.as_str(), merges.as_deref()))
+        .collect();
+    Ok(py.detach(|| {
+        drift_rust_core::process_export_payload_batch(&items, &options)
+            .into_iter()
+            .map(|result| batch_item(result.map(export_payload_tuple)))
+            .collect()
+    }))
+}
+
+#[pyfunction]
+#[pyo3(signature = (payload, schema_merges=None, options=None))]
 #[allow(clippy::type_complexity)]
 pub fn process_export_payload_pyobject(
     py: Python<'_>,
     payload: &Bound<'_, PyAny>,
     schema_merges: Option<&Bound<'_, PyAny>>,
+    options: Option<&Bound<'_, PyAny>>,
 ) -> PyResult<(Py<PyAny>, String, Py<PyAny>, String, Vec<u8>)> {
-    let payload_value = py_to_json_value(payload)?;
-    let schema_merges_json = if let Some(merges) = schema_merges {
-        let merges_value = py_to_json_value(merges)?;
-        Some(serde_json::to_string(&merges_value).map_err(|e| {
-            pyo3::exceptions::PyValueError::new_err(format!("invalid schema merges: {e}"))
-        })?)
-    } else {
-        None
-    };
-
-    drift_rust_core::process_export_payload_value(&payload_value, schema_merges_json.as_deref())
-        .and_then(|result| {
-            let normalized_py = json_value_to_py(py, &result.normalized_value)
-                .map_err(|e| drift_rust_core::CoreError::SerializationError(e.to_string()))?;
-            let schema_py = json_value_to_py(py, &result.decoded_schema_value)
-                .map_err(|e| drift_rust_core::CoreError::SerializationError(e.to_string()))?;
-            Ok((
-                normalized_py,
-                result.decoded_value_hash,
-                schema_py,
-                result.decoded_schema_hash,
-                result.protobuf_struct_bytes,
-            ))
-        })
+    let schema_merges_json = py_any_to_optional_json_string(schema_merges, "schema merges")?;
+    let options_json = py_any_to_optional_json_string(options, "options")?;
+    let options = export_payload_options(options_json.as_deref())?;
+    let payload_value = py_to_json_value(payload, exact_numbers(&options))?;
+
+    drift_rust_core::process_export_payload_value_with_options(
+        &payload_value,
+        schema_merges_json.as_deref(),
+        &options,
+    )
+    .and_then(|result| {
+        let normalized_py = json_value_to_py(py, &result.normalized_value)
+            .map_err(|e| drift_rust_core::CoreError::SerializationError(e.to_string()))?;
+        let schema_py = json_value_to_py(py, &result.decoded_schema_value)
+            .map_err(|e| drift_rust_core::CoreError::SerializationError(e.to_string()))?;
+        Ok((
+            normalized_py,
+            result.decoded_value_hash,
+            schema_py,
+            result.decoded_schema_hash,
+            result.protobuf_struct_bytes,
+        ))
+    })
+    .map_err(map_core_err)
+}
+
+/// Like `process_export_payload_pyobject`, but returns every result field
+/// (including the detector redaction report) as a dict.
+#[pyfunction]
+#[pyo3(signature = (payload, schema_merges=None, options=None))]
+pub fn process_export_payload_detailed<'py>(
+    py: Python<'py>,
+    payload: &Bound<'py, PyAny>,
+    schema_merges: Option<&Bound<'py, PyAny>>,
+    options: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let schema_merges_json = py_any_to_optional_json_string(schema_merges, "schema merges")?;
+    let options_json = py_any_to_optional_json_string(options, "options")?;
+    let options = export_payload_options(options_json.as_deref())?;
+    let payload_value = py_to_json_value(payload, exact_numbers(&options))?;
+    let result = drift_rust_core::process_export_payload_value_with_options(
+        &payload_value,
+        schema_merges_json.as_deref(),
+        &options,
+    )
+    .map_err(map_core_err)?;
+
+    let redactions = PyList::empty(py);
+    for redaction in &result.redactions {
+        let entry = PyDict::new(py);
+        entry.set_item("path", &redaction.path)?;
+        entry.set_item("detector", redaction.detector.as_str())?;
+        redactions.append(entry)?;
+    }
+    let out = PyDict::new(py);
+    out.set_item(
+        "normalized_value",
+        json_value_to_py(py, &result.normalized_value)?,
+    )?;
+    out.set_item(
+        "decoded_value",
+        json_value_to_py(py, &result.decoded_value)?,
+    )?;
+    out.set_item("decoded_value_hash", result.decoded_value_hash)?;
+    out.set_item("match_hash", result.match_hash)?;
+    out.set_item("importance_hash", result.importance_hash)?;
+    out.set_item("fingerprint", result.fingerprint)?;
+    out.set_item("path_hashes", result.path_hashes)?;
+    out.set_item(
+        "decoded_schema",
+        json_value_to_py(py, &result.decoded_schema_value)?,
+    )?;
+    out.set_item("decoded_schema_hash", result.decoded_schema_hash)?;
+    out.set_item("shape_hash", result.shape_hash)?;
+    out.set_item(
+        "hash_scheme",
+        result.hash_scheme.map(|scheme| scheme.as_str()),
+    )?;
+    out.set_item(
+        "protobuf_struct_bytes",
+        PyBytes::new(py, &result.protobuf_struct_bytes),
+    )?;
+    out.set_item("redactions", redactions)?;
+    Ok(out)
+}
+
+#[pyfunction]
+#[pyo3(signature = (payloads_json, state_json=None, schema_merges_json=None, options_json=None))]
+pub fn accumulate_payload_schemas(
+    py: Python<'_>,
+    payloads_json: Vec<String>,
+    state_json: Option<&str>,
+    schema_merges_json: Option<Vec<Option<String>>>,
+    options_json: Option<&str>,
+) -> PyResult<String> {
+    let options = export_payload_options(options_json)?;
+    let merges = schema_merges_per_payload(payloads_json.len(), schema_merges_json)?;
+    let items: Vec<(&str, Option<&str>)> = payloads_json
+        .iter()
+        .zip(&merges)
+        .map(|(payload, merges)| (payload.as_str(), merges.as_deref()))
+        .collect();
+    py.detach(|| drift_rust_core::accumulate_payload_schemas(state_json, &items, &options))
         .map_err(map_core_err)
 }
 
+#[pyfunction]
+#[pyo3(signature = (schemas_json, state_json=None))]
+pub fn accumulate_schemas(schemas_json: Vec<String>, state_json: Option<&str>) -> PyResult<String> {
+    let schemas: Vec<&str> = schemas_json.iter().map(String::as_str).collect();
+    drift_rust_core::accumulate_schemas(state_json, &schemas).map_err(map_core_err)
+}
+
+#[pyfunction]
+pub fn merge_schema_accumulators(states_json: Vec<String>) -> PyResult<String> {
+    let states: Vec<&str> = states_json.iter().map(String::as_str).collect();
+    drift_rust_core::merge_schema_accumulators(&states).map_err(map_core_err)
+}
+
+#[pyfunction]
+pub fn drift_schema_to_json_schema(schema_json: &str) -> PyResult<String> {
+    drift_rust_core::drift_schema_to_json_schema(schema_json).map_err(map_core_err)
+}
+
+#[pyfunction]
+pub fn json_schema_to_drift_schema(json_schema: &str) -> PyResult<String> {
+    drift_rust_core::json_schema_to_drift_schema(json_schema).map_err(map_core_err)
+}
+
+#[pyfunction]
+pub fn drift_schema_proto_to_json_schema(schema_proto_bytes: &[u8]) -> PyResult<String> {
+    drift_rust_core::drift_schema_proto_to_json_schema(schema_proto_bytes).map_err(map_core_err)
+}
+
+#[pyfunction]
+pub fn json_schema_to_drift_schema_proto(json_schema: &str) -> PyResult<Vec<u8>> {
+    drift_rust_core::json_schema_to_drift_schema_proto(json_schema).map_err(map_core_err)
+}
+
+/// Schema changes as dicts with `path`, `kind`, `breaking`, `before` and
+/// `after`.
+#[pyfunction]
+pub fn diff_schemas<'py>(
+    py: Python<'py>,
+    old_schema_json: &str,
+    new_schema_json: &str,
+) -> PyResult<Bound<'py, PyList>> {
+    let changes =
+        drift_rust_core::diff_schemas(old_schema_json, new_schema_json).map_err(map_core_err)?;
+    let out = PyList::empty(py);
+    for change in changes {
+        let entry = PyDict::new(py);
+        entry.set_item("path", change.path)?;
+        entry.set_item("kind", change.kind.as_str())?;
+        entry.set_item("breaking", change.breaking)?;
+        let before = change
+            .before
+            .map(|v| json_value_to_py(py, &v))
+            .transpose()?;
+        let after = change.after.map(|v| json_value_to_py(py, &v)).transpose()?;
+        entry.set_item("before", before)?;
+        entry.set_item("after", after)?;
+        out.append(entry)?;
+    }
+    Ok(out)
+}
+
+fn schema_violations_to_py<'py>(
+    py: Python<'py>,
+    violations: Vec<drift_rust_core::SchemaViolation>,
+) -> PyResult<Bound<'py, PyList>> {
+    let out = PyList::empty(py);
+    for violation in violations {
+        let entry = PyDict::new(py);
+        entry.set_item("path", violation.path)?;
+        entry.set_item("expected", violation.expected)?;
+        entry.set_item("actual", violation.actual)?;
+        out.append(entry)?;
+    }
+    Ok(out)
+}
+
+/// Violations as dicts with `path`, `expected` and `actual` type codes.
+#[pyfunction]
+pub fn validate_against_schema<'py>(
+    py: Python<'py>,
+    value_json: &str,
+    schema_json: &str,
+) -> PyResult<Bound<'py, PyList>> {
+    let violations =
+        drift_rust_core::validate_against_schema(value_json, schema_json).map_err(map_core_err)?;
+    schema_violations_to_py(py, violations)
+}
+
+#[pyfunction]
+pub fn validate_against_schema_proto<'py>(
+    py: Python<'py>,
+    value_json: &str,
+    schema_proto_bytes: &[u8],
+) -> PyResult<Bound<'py, PyList>> {
+    let violations = drift_rust_core::validate_against_schema_proto(value_json, schema_proto_bytes)
+        .map_err(map_core_err)?;
+    schema_violations_to_py(py, violations)
+}
+
+#[pyfunction]
+pub fn fingerprint_similarity(a: &str, b: &str) -> PyResult<f64> {
+    drift_rust_core::fingerprint_similarity(a, b).map_err(map_core_err)
+}
+
 #[pyfunction]
 #[allow(clippy::too_many_arguments)]
 pub fn build_span_proto_bytes_pyobject(
@@ -120,8 +446,8 @@ pub fn build_span_proto_bytes_pyobject(
     input_value_proto_struct_bytes: Option<&Bound<'_, PyAny>>,
     output_value_proto_struct_bytes: Option<&Bound<'_, PyAny>>,
 ) -> PyResult<Vec<u8>> {
-    let input_schema_value = py_to_json_value(input_schema)?;
-    let output_schema_value = py_to_json_value(output_schema)?;
+    let input_schema_value = py_to_json_value(input_schema, false)?;
+    let output_schema_value = py_to_json_value(output_schema, false)?;
     let metadata_value = py_any_to_optional_json(metadata)?;
     let input_value_json = py_any_to_optional_json(input_value)?;
     let output_value_json = py_any_to_optional_json(output_value)?;
@@ -2,7 +2,11 @@ use pyo3::prelude::*;
 use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
 use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
 
-pub fn py_to_json_value(value: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
+/// Converts a Python object to JSON. Ints beyond `i64` and `Decimal`s read
+/// as floats (NaN and infinities as `null`) unless `exact_numbers` keeps
+/// their digits, for payloads processed with lossless numbers or hash scheme
+/// `v2`.
+pub fn py_to_json_value(value: &Bound<'_, PyAny>, exact_numbers: bool) -> PyResult<JsonValue> {
     if value.is_none() {
         return Ok(JsonValue::Null);
     }
@@ -12,8 +16,14 @@ pub fn py_to_json_value(value: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
     if let Ok(v) = value.extract::<i64>() {
         return Ok(JsonValue::Number(JsonNumber::from(v)));
     }
-    if let Ok(v) = value.extract::<f64>() {
-        return Ok(JsonNumber::from_f64(v)
+    if exact_numbers && value.is_instance_of::<PyInt>() {
+        let digits = value.str()?.to_string();
+        return drift_rust_core::number_from_literal(&digits).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("invalid int: {digits}"))
+        });
+    }
+    if let Ok(v) = value.cast::<PyFloat>() {
+        return Ok(JsonNumber::from_f64(v.value())
             .map(JsonValue::Number)
             .unwrap_or(JsonValue::Null));
     }
@@ -23,14 +33,14 @@ pub fn py_to_json_value(value: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
     if let Ok(list) = value.cast::<PyList>() {
         let mut out = Vec::with_capacity(list.len());
         for item in list.iter() {
-            out.push(py_to_json_value(&item)?);
+            out.push(py_to_json_value(&item, exact_numbers)?);
         }
         return Ok(JsonValue::Array(out));
     }
     if let Ok(tuple) = value.cast::<PyTuple>() {
         let mut out = Vec::with_capacity(tuple.len());
         for item in tuple.iter() {
-            out.push(py_to_json_value(&item)?);
+            out.push(py_to_json_value(&item, exact_numbers)?);
         }
         return Ok(JsonValue::Array(out));
     }
@@ -38,30 +48,62 @@ pub fn py_to_json_value(value: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
         let mut out = JsonMap::with_capacity(dict.len());
         for (k, v) in dict.iter() {
             let key = k.extract::<String>()?;
-            out.insert(key, py_to_json_value(&v)?);
+            out.insert(key, py_to_json_value(&v, exact_numbers)?);
         }
         return Ok(JsonValue::Object(out));
     }
+    if exact_numbers && value.is_instance(&decimal_type(value.py())?)? {
+        // Checked before the generic f64 extraction below because Decimal
+        // implements __float__.
+        let literal = value.str()?.to_string();
+        return drift_rust_core::number_from_literal(&literal).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("invalid decimal: {literal}"))
+        });
+    }
+    if let Ok(v) = value.extract::<f64>() {
+        return Ok(JsonNumber::from_f64(v)
+            .map(JsonValue::Number)
+            .unwrap_or(JsonValue::Null));
+    }
     Err(pyo3::exceptions::PyTypeError::new_err(
         "unsupported value type for Rust export payload processing",
     ))
 }
 
+fn decimal_type(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    py.import("decimal")?.getattr("Decimal")
+}
+
+fn json_number_to_py(py: Python<'_>, v: &JsonNumber) -> PyResult<Py<PyAny>> {
+    if let Some(i) = v.as_i64() {
+        Ok(PyInt::new(py, i).unbind().into_any())
+    } else if let Some(u) = v.as_u64() {
+        Ok(PyInt::new(py, u).unbind().into_any())
+    } else if let Some(f) = v.as_f64() {
+        Ok(PyFloat::new(py, f).unbind().into_any())
+    } else {
+        Ok(py.None())
+    }
+}
+
+/// Lossless numbers: integers wider than 64 bits become `int`, decimals that
+/// would change as a float become `Decimal`.
+fn lossless_number_to_py(py: Python<'_>, literal: &str) -> PyResult<Py<PyAny>> {
+    if literal.contains(['.', 'e', 'E']) {
+        return Ok(decimal_type(py)?.call1((literal,))?.unbind());
+    }
+    let int_type = py.import("builtins")?.getattr("int")?;
+    Ok(int_type.call1((literal,))?.unbind())
+}
+
 pub fn json_value_to_py(py: Python<'_>, value: &JsonValue) -> PyResult<Py<PyAny>> {
+    if let Some(literal) = drift_rust_core::lossless_literal(value) {
+        return lossless_number_to_py(py, literal);
+    }
     match value {
         JsonValue::Null => Ok(py.None()),
         JsonValue::Bool(v) => Ok(PyBool::new(py, *v).to_owned().unbind().into_any()),
-        JsonValue::Number(v) => {
-            if let Some(i) = v.as_i64() {
-                Ok(PyInt::new(py, i).unbind().into_any())
-            } else if let Some(u) = v.as_u64() {
-                Ok(PyInt::new(py, u).unbind().into_any())
-            } else if let Some(f) = v.as_f64() {
-                Ok(PyFloat::new(py, f).unbind().into_any())
-            } else {
-                Ok(py.None())
-            }
-        }
+        JsonValue::Number(v) => json_number_to_py(py, v),
         JsonValue::String(v) => Ok(PyString::new(py, v).unbind().into_any()),
         JsonValue::Array(arr) => {
             let list = PyList::empty(py);
@@ -82,11 +124,24 @@ pub fn json_value_to_py(py: Python<'_>, value: &JsonValue) -> PyResult<Py<PyAny>
 
 pub fn py_any_to_optional_json(value: Option<&Bound<'_, PyAny>>) -> PyResult<Option<JsonValue>> {
     match value {
-        Some(v) if !v.is_none() => Ok(Some(py_to_json_value(v)?)),
+        Some(v) if !v.is_none() => Ok(Some(py_to_json_value(v, false)?)),
         _ => Ok(None),
     }
 }
 
+pub fn py_any_to_optional_json_string(
+    value: Option<&Bound<'_, PyAny>>,
+    label: &str,
+) -> PyResult<Option<String>> {
+    py_any_to_optional_json(value)?
+        .map(|v| {
+            serde_json::to_string(&v).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("invalid {label}: {e}"))
+            })
+        })
+        .transpose()
+}
+
 pub fn py_any_to_optional_bytes(value: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Vec<u8>>> {
     match value {
         Some(v) if !v.is_none() => {
@@ -8,14 +8,30 @@ use pyo3::prelude::*;
 fn drift_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(api::normalize_json, m)?)?;
     m.add_function(wrap_pyfunction!(api::deterministic_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(api::hash_schemes, m)?)?;
+    m.add_function(wrap_pyfunction!(api::default_hash_scheme, m)?)?;
     m.add_function(wrap_pyfunction!(api::normalize_and_hash, m)?)?;
     m.add_function(wrap_pyfunction!(api::object_to_protobuf_struct_bytes, m)?)?;
     m.add_function(wrap_pyfunction!(
         api::object_to_protobuf_struct_field_count,
         m
     )?)?;
+    m.add_function(wrap_pyfunction!(api::normalize_and_hash_batch, m)?)?;
     m.add_function(wrap_pyfunction!(api::process_export_payload, m)?)?;
+    m.add_function(wrap_pyfunction!(api::process_export_payload_batch, m)?)?;
     m.add_function(wrap_pyfunction!(api::process_export_payload_pyobject, m)?)?;
+    m.add_function(wrap_pyfunction!(api::process_export_payload_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(api::drift_schema_to_json_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(api::json_schema_to_drift_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(api::drift_schema_proto_to_json_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(api::json_schema_to_drift_schema_proto, m)?)?;
+    m.add_function(wrap_pyfunction!(api::diff_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(api::validate_against_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(api::validate_against_schema_proto, m)?)?;
+    m.add_function(wrap_pyfunction!(api::fingerprint_similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(api::accumulate_payload_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(api::accumulate_schemas, m)?)?;
+    m.add_function(wrap_pyfunction!(api::merge_schema_accumulators, m)?)?;
     m.add_function(wrap_pyfunction!(api::build_span_proto_bytes_pyobject, m)?)?;
     m.add_function(wrap_pyfunction!(
         api::build_export_spans_request_bytes_pyobject,
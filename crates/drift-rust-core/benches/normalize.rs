@@ -0,0 +1,68 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use drift_rust_core::NormalizeOptions;
+use serde_json::{Value as JsonValue, json};
+use std::hint::black_box;
+
+/// A recorded HTTP response with a few hundred KB of JSON body, the shape
+/// that dominates export payload processing.
+fn large_http_body() -> JsonValue {
+    let items: Vec<JsonValue> = (0..2_000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "uuid": format!("3f2b8c1e-9d4a-4e6b-8f1a-{i:012}"),
+                "name": format!("Item {i}"),
+                "price": i as f64 * 1.25,
+                "tags": ["alpha", "beta", "gamma"],
+                "attributes": {"color": "red", "size": i % 7, "in_stock": i % 2 == 0},
+            })
+        })
+        .collect();
+    json!({
+        "statusCode": 200,
+        "headers": {"content-type": "application/json", "x-request-id": "abc123"},
+        "body": {"items": items, "total": 2_000, "next": null},
+    })
+}
+
+/// What normalization cost before the in-place canonicalizer: a full
+/// serialize and re-parse of the value.
+fn serialize_parse_round_trip(value: &JsonValue) -> JsonValue {
+    let serialized = serde_json::to_string(value).expect("value should serialize");
+    serde_json::from_str(&serialized).expect("serialized value should parse")
+}
+
+fn normalize_benchmarks(c: &mut Criterion) {
+    let body = large_http_body();
+    let body_json = serde_json::to_string(&body).expect("body should serialize");
+
+    let mut group = c.benchmark_group("large_http_body");
+    group.bench_function("serialize_parse_round_trip", |b| {
+        b.iter(|| serialize_parse_round_trip(black_box(&body)))
+    });
+    // Takes the value by move like the export path does, so the clone is
+    // setup and not timed.
+    group.bench_function("normalize_json_value", |b| {
+        b.iter_batched(
+            || body.clone(),
+            |value| {
+                drift_rust_core::normalize_json_value(value, &NormalizeOptions::default())
+                    .expect("normalization should work")
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("normalize_json", |b| {
+        b.iter(|| drift_rust_core::normalize_json(black_box(&body_json)).expect("valid json"))
+    });
+    group.bench_function("process_export_payload_value", |b| {
+        b.iter(|| {
+            drift_rust_core::process_export_payload_value(black_box(&body), None)
+                .expect("processing should work")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, normalize_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,179 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::{CoreError, CoreResult};
+use crate::normalize::parse_json;
+use crate::payload::process_export_payload_text;
+use crate::schema::merge_schemas;
+use crate::types::ExportPayloadOptions;
+
+/// A decoded schema folded over many samples. Properties not seen in every
+/// object sample are `optional`, fields seen with several types list them in
+/// `types` and fields seen as null are `nullable` (see `merge_schemas` in
+/// `schema.rs`). The JSON form is the resumable state.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaAccumulator {
+    pub samples: u64,
+    pub schema: Option<JsonValue>,
+}
+
+impl SchemaAccumulator {
+    pub fn from_json(state_json: &str) -> CoreResult<Self> {
+        serde_json::from_str(state_json).map_err(|e| CoreError::InvalidJson(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> CoreResult<String> {
+        serde_json::to_string(self).map_err(|e| CoreError::SerializationError(e.to_string()))
+    }
+
+    /// Folds in one sample's decoded schema.
+    pub fn add_schema(&mut self, schema: JsonValue) {
+        self.merge(SchemaAccumulator {
+            samples: 1,
+            schema: Some(schema),
+        });
+    }
+
+    /// Folds in another accumulator, e.g. one built on another worker.
+    pub fn merge(&mut self, other: SchemaAccumulator) {
+        self.samples += other.samples;
+        self.schema = match (self.schema.take(), other.schema) {
+            (Some(a), Some(b)) => Some(merge_schemas(a, b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+fn accumulator(state_json: Option<&str>) -> CoreResult<SchemaAccumulator> {
+    state_json
+        .map(SchemaAccumulator::from_json)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Adds the decoded schema of each `(payload, schema merges)` pair, as
+/// [`crate::process_export_payload_with_options`] would produce it, to the
+/// accumulator in `state_json` (a new one when `None`) and returns the new
+/// state. Payloads are processed on the rayon thread pool; the first bad
+/// payload fails the whole call.
+pub fn accumulate_payload_schemas(
+    state_json: Option<&str>,
+    items: &[(&str, Option<&str>)],
+    options: &ExportPayloadOptions,
+) -> CoreResult<String> {
+    let mut state = accumulator(state_json)?;
+    let schemas = items
+        .par_iter()
+        .map(|(payload, merges)| {
+            process_export_payload_text(payload, *merges, options)
+                .map(|result| result.decoded_schema_value)
+        })
+        .collect::<CoreResult<Vec<_>>>()?;
+    schemas
+        .into_iter()
+        .for_each(|schema| state.add_schema(schema));
+    state.to_json()
+}
+
+/// Adds existing decoded schemas (`decoded_schema_json` outputs) to the
+/// accumulator in `state_json` and returns the new state.
+pub fn accumulate_schemas(state_json: Option<&str>, schemas_json: &[&str]) -> CoreResult<String> {
+    let mut state = accumulator(state_json)?;
+    for schema_json in schemas_json {
+        state.add_schema(parse_json(schema_json)?);
+    }
+    state.to_json()
+}
+
+/// Combines accumulator states built separately into one.
+pub fn merge_schema_accumulators(states_json: &[&str]) -> CoreResult<String> {
+    let mut state = SchemaAccumulator::default();
+    for state_json in states_json {
+        state.merge(SchemaAccumulator::from_json(state_json)?);
+    }
+    state.to_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn payload_samples_track_optional_nullable_and_mixed_fields() {
+        let state = accumulate_payload_schemas(
+            None,
+            &[
+                (r#"{"id":1,"name":"a","tag":"x"}"#, None),
+                (r#"{"id":"2","name":null}"#, None),
+            ],
+            &ExportPayloadOptions::default(),
+        )
+        .expect("accumulation should succeed");
+        let state = accumulate_payload_schemas(
+            Some(&state),
+            &[(r#"{"id":3,"name":"c"}"#, None)],
+            &ExportPayloadOptions::default(),
+        )
+        .expect("accumulation should resume");
+        let state = SchemaAccumulator::from_json(&state).expect("state should parse");
+
+        assert_eq!(state.samples, 3);
+        assert_eq!(
+            state.schema,
+            Some(json!({
+                "type": 6,
+                "properties": {
+                    "id": {"type": 0, "types": [1, 2], "properties": {}},
+                    "name": {"type": 2, "nullable": true, "properties": {}},
+                    "tag": {"type": 2, "properties": {}, "optional": true}
+                }
+            }))
+        );
+    }
+
+    #[test]
+    fn payloads_fail_exactly_where_export_processing_does() {
+        let options = ExportPayloadOptions::default();
+        for depth in 125..=130 {
+            let payload = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+            let accumulated = accumulate_payload_schemas(None, &[(&payload, None)], &options);
+            let processed = crate::process_export_payload_with_options(&payload, None, &options);
+            assert_eq!(
+                accumulated.map(|_| ()).map_err(|e| e.to_string()),
+                processed.map(|_| ()).map_err(|e| e.to_string()),
+                "depth {depth}"
+            );
+        }
+    }
+
+    #[test]
+    fn split_accumulators_merge_to_the_same_state() {
+        let schemas = [
+            r#"{"type":6,"properties":{"a":{"type":1,"properties":{}}},"encoding":1}"#,
+            r#"{"type":6,"properties":{"b":{"type":3,"properties":{}}}}"#,
+            r#"{"type":4,"properties":{}}"#,
+        ];
+        let all = accumulate_schemas(None, &schemas).expect("accumulation should succeed");
+        let left = accumulate_schemas(None, &schemas[..1]).expect("accumulation should succeed");
+        let right = accumulate_schemas(None, &schemas[1..]).expect("accumulation should succeed");
+        let merged = merge_schema_accumulators(&[&left, &right]).expect("merging should succeed");
+
+        assert_eq!(merged, all);
+        let state = SchemaAccumulator::from_json(&all).expect("state should parse");
+        assert_eq!(
+            state.schema.as_ref().map(|s| &s["nullable"]),
+            Some(&json!(true))
+        );
+        assert_eq!(
+            state.schema.as_ref().map(|s| &s["encoding"]),
+            Some(&json!(1))
+        );
+        assert!(matches!(
+            accumulate_schemas(Some("{bad"), &[]),
+            Err(CoreError::InvalidJson(_))
+        ));
+    }
+}
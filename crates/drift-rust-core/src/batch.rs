@@ -0,0 +1,80 @@
+use rayon::prelude::*;
+
+use crate::error::CoreResult;
+use crate::hash::{HashOptions, normalize_and_hash_with_options};
+use crate::payload::process_export_payload_with_options;
+use crate::types::{ExportPayloadOptions, ExportPayloadResult};
+
+/// [`normalize_and_hash_with_options`] over many payloads on the rayon
+/// thread pool. Results keep input order and a bad payload only fails its own
+/// entry.
+pub fn normalize_and_hash_batch(
+    payloads: &[&str],
+    options: &HashOptions,
+) -> Vec<CoreResult<(String, String)>> {
+    payloads
+        .par_iter()
+        .map(|payload| normalize_and_hash_with_options(payload, options))
+        .collect()
+}
+
+/// [`process_export_payload_with_options`] over `(payload, schema merges)`
+/// pairs on the rayon thread pool, with results in input order.
+pub fn process_export_payload_batch(
+    items: &[(&str, Option<&str>)],
+    options: &ExportPayloadOptions,
+) -> Vec<CoreResult<ExportPayloadResult>> {
+    items
+        .par_iter()
+        .map(|(payload, merges)| process_export_payload_with_options(payload, *merges, options))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CoreError;
+    use crate::hash::normalize_and_hash;
+
+    #[test]
+    fn batch_results_keep_order_and_per_item_errors() {
+        let payloads: Vec<String> = (0..64).map(|i| format!(r#"{{"i":{i}}}"#)).collect();
+        let mut inputs: Vec<&str> = payloads.iter().map(String::as_str).collect();
+        inputs[7] = "{bad";
+        let results = normalize_and_hash_batch(&inputs, &HashOptions::default());
+
+        assert_eq!(results.len(), 64);
+        assert!(matches!(results[7], Err(CoreError::InvalidJson(_))));
+        for (i, result) in results.iter().enumerate().filter(|(i, _)| *i != 7) {
+            assert_eq!(
+                result.as_ref().expect("item should succeed"),
+                &normalize_and_hash(&payloads[i]).expect("item should succeed")
+            );
+        }
+    }
+
+    #[test]
+    fn export_batch_applies_per_item_schema_merges() {
+        let results = process_export_payload_batch(
+            &[
+                (
+                    r#"{"b":"eyJrIjoidiJ9"}"#,
+                    Some(r#"{"b":{"encoding":1,"decoded_type":1}}"#),
+                ),
+                (r#"{"b":"eyJrIjoidiJ9"}"#, None),
+            ],
+            &ExportPayloadOptions::default(),
+        );
+
+        let decoded: Vec<&str> = results
+            .iter()
+            .map(|r| {
+                r.as_ref()
+                    .expect("item should succeed")
+                    .decoded_json
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(decoded, [r#"{"b":{"k":"v"}}"#, r#"{"b":"eyJrIjoidiJ9"}"#]);
+    }
+}
@@ -0,0 +1,541 @@
+//! Structured forms of text bodies, selected by `MergeRule.decoded_type`
+//! (the drift `DecodedType` codes). Every mapping is deterministic and has an
+//! inverse that decodes back to the same value:
+//!
+//! - JSON (`1`): parsed as is, numbers read like the payload's own.
+//! - XML (`5`): `{"<root tag>": element}`. An element with attributes or
+//!   child elements is an object holding `"@<name>"` attributes, child
+//!   elements under their qualified tag names (repeated tags become arrays in
+//!   document order) and its trimmed text under `"#text"` when non-empty.
+//!   Any other element is its trimmed text, `""` when empty. Entities and
+//!   CDATA are resolved into text; comments, processing instructions and the
+//!   declaration are dropped. Documents nesting elements deeper than the
+//!   core's nesting bound do not decode.
+//! - YAML (`6`): the JSON data model of the document.
+//! - CSV (`8`): an array with one object per row, keyed by the header row.
+//!   Fields stay strings.
+//! - Form data (`12`, `application/x-www-form-urlencoded`): an object of
+//!   decoded names to string values; repeated names become arrays in order.
+//! - Multipart (`13`): an object of part names, as for form data. A part
+//!   with a filename or content type is
+//!   `{"filename", "content_type", "content"}` (absent ones omitted), any
+//!   other part is its content. The boundary comes from the first line.
+
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::error::{CoreError, CoreResult};
+use crate::limits::MAX_NESTING;
+use crate::normalize::{NumberParsing, parse_payload};
+use crate::numbers::to_json_string;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DecodedType {
+    Json,
+    Xml,
+    Yaml,
+    Csv,
+    FormData,
+    Multipart { boundary: String },
+}
+
+impl DecodedType {
+    /// Parses `text` as the type with `code`, or `None` when the code is
+    /// unknown or `text` is not valid for it.
+    pub(crate) fn decode(
+        code: i32,
+        text: &str,
+        numbers: NumberParsing,
+    ) -> Option<(JsonValue, Self)> {
+        Some(match code {
+            1 => (parse_payload(text, numbers).ok()?, Self::Json),
+            5 => (decode_xml(text)?, Self::Xml),
+            6 => (serde_yaml_ng::from_str(text).ok()?, Self::Yaml),
+            8 => (decode_csv(text)?, Self::Csv),
+            12 => (decode_form(text), Self::FormData),
+            13 => {
+                let (value, boundary) = decode_multipart(text)?;
+                (value, Self::Multipart { boundary })
+            }
+            _ => return None,
+        })
+    }
+
+    /// Inverse of [`DecodedType::decode`]. Formatting, key order and, for
+    /// CSV, column order need not match the original text.
+    pub(crate) fn encode(&self, value: &JsonValue) -> CoreResult<String> {
+        let ser_err = |e: String| CoreError::SerializationError(e);
+        match self {
+            Self::Json => to_json_string(value),
+            Self::Xml => encode_xml(value),
+            Self::Yaml => {
+                serde_yaml_ng::to_string(&to_yaml(value)).map_err(|e| ser_err(e.to_string()))
+            }
+            Self::Csv => encode_csv(value),
+            Self::FormData => {
+                let mut out = form_urlencoded::Serializer::new(String::new());
+                for (name, field) in repeated_fields(value)? {
+                    out.append_pair(name, &field_text(field));
+                }
+                Ok(out.finish())
+            }
+            Self::Multipart { boundary } => encode_multipart(value, boundary),
+        }
+    }
+}
+
+/// Adds `value` under `key`, turning repeated keys into arrays.
+fn insert_repeated(map: &mut Map<String, JsonValue>, key: String, value: JsonValue) {
+    match map.get_mut(&key) {
+        Some(JsonValue::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = JsonValue::Array(vec![first, value]);
+        }
+        None => {
+            map.insert(key, value);
+        }
+    }
+}
+
+/// `(name, value)` pairs of a form-like object, with arrays spread back into
+/// repeated names.
+fn repeated_fields(value: &JsonValue) -> CoreResult<Vec<(&str, &JsonValue)>> {
+    let JsonValue::Object(map) = value else {
+        return Err(CoreError::SerializationError(
+            "form fields must be an object".to_string(),
+        ));
+    };
+    Ok(map
+        .iter()
+        .flat_map(|(name, field)| match field {
+            JsonValue::Array(values) => values.iter().map(|v| (name.as_str(), v)).collect(),
+            _ => vec![(name.as_str(), field)],
+        })
+        .collect())
+}
+
+/// Text of a scalar field; values that are not strings (e.g. after a
+/// redaction replaced one) are written as JSON.
+fn field_text(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn decode_form(text: &str) -> JsonValue {
+    let mut map = Map::new();
+    for (name, value) in form_urlencoded::parse(text.as_bytes()) {
+        insert_repeated(&mut map, name.into_owned(), value.into_owned().into());
+    }
+    JsonValue::Object(map)
+}
+
+fn decode_xml(text: &str) -> Option<JsonValue> {
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<(String, Map<String, JsonValue>, String)> = Vec::new();
+    let mut root = None;
+    loop {
+        let event = reader.read_event().ok()?;
+        match event {
+            Event::Start(start) => {
+                // Stop before building a value too deep to walk, or even to
+                // drop, recursively.
+                if stack.len() >= MAX_NESTING {
+                    return None;
+                }
+                stack.push(xml_element(&start)?);
+            }
+            Event::Empty(start) => {
+                let element = xml_element(&start)?;
+                close_xml_element(element, &mut stack, &mut root)?;
+            }
+            Event::End(_) => {
+                let element = stack.pop()?;
+                close_xml_element(element, &mut stack, &mut root)?;
+            }
+            Event::Text(t) => {
+                if let Some((_, _, text)) = stack.last_mut() {
+                    text.push_str(&t.decode().ok()?);
+                }
+            }
+            Event::CData(t) => {
+                if let Some((_, _, text)) = stack.last_mut() {
+                    text.push_str(&t.decode().ok()?);
+                }
+            }
+            Event::GeneralRef(r) => {
+                let resolved = match r.resolve_char_ref().ok()? {
+                    Some(c) => c.to_string(),
+                    None => {
+                        let name = r.decode().ok()?;
+                        quick_xml::escape::resolve_predefined_entity(&name)?.to_string()
+                    }
+                };
+                if let Some((_, _, text)) = stack.last_mut() {
+                    text.push_str(&resolved);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !stack.is_empty() {
+        return None;
+    }
+    root
+}
+
+fn xml_element(start: &BytesStart) -> Option<(String, Map<String, JsonValue>, String)> {
+    let name = String::from_utf8(start.name().as_ref().to_vec()).ok()?;
+    let mut attributes = Map::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.ok()?;
+        let key = String::from_utf8(attribute.key.as_ref().to_vec()).ok()?;
+        let value = attribute.unescape_value().ok()?.into_owned();
+        attributes.insert(format!("@{key}"), value.into());
+    }
+    Some((name, attributes, String::new()))
+}
+
+/// Folds a finished element into its parent, or makes it the root. A second
+/// root element is not well-formed XML.
+fn close_xml_element(
+    (name, mut map, text): (String, Map<String, JsonValue>, String),
+    stack: &mut [(String, Map<String, JsonValue>, String)],
+    root: &mut Option<JsonValue>,
+) -> Option<()> {
+    let text = text.trim();
+    let value = if map.is_empty() {
+        JsonValue::String(text.to_string())
+    } else {
+        if !text.is_empty() {
+            map.insert("#text".to_string(), text.into());
+        }
+        JsonValue::Object(map)
+    };
+    match stack.last_mut() {
+        Some((_, parent, _)) => insert_repeated(parent, name, value),
+        None if root.is_none() => {
+            let mut document = Map::new();
+            document.insert(name, value);
+            *root = Some(JsonValue::Object(document));
+        }
+        None => return None,
+    }
+    Some(())
+}
+
+fn encode_xml(value: &JsonValue) -> CoreResult<String> {
+    match value {
+        JsonValue::Object(document) if document.len() == 1 => {
+            let mut out = String::new();
+            for (name, element) in document {
+                write_xml_element(name, element, &mut out);
+            }
+            Ok(out)
+        }
+        _ => Err(CoreError::SerializationError(
+            "an XML document must be an object with a single root element".to_string(),
+        )),
+    }
+}
+
+fn write_xml_element(name: &str, element: &JsonValue, out: &mut String) {
+    if let JsonValue::Array(elements) = element {
+        for element in elements {
+            write_xml_element(name, element, out);
+        }
+        return;
+    }
+    out.push('<');
+    out.push_str(name);
+    let JsonValue::Object(map) = element else {
+        out.push('>');
+        out.push_str(&escape(field_text(element)));
+        out.push_str(&format!("</{name}>"));
+        return;
+    };
+    for (key, attribute) in map {
+        if let Some(attribute_name) = key.strip_prefix('@') {
+            out.push_str(&format!(
+                " {attribute_name}=\"{}\"",
+                escape(field_text(attribute))
+            ));
+        }
+    }
+    out.push('>');
+    if let Some(text) = map.get("#text") {
+        out.push_str(&escape(field_text(text)));
+    }
+    for (key, child) in map {
+        if !key.starts_with('@') && key != "#text" {
+            write_xml_element(key, child, out);
+        }
+    }
+    out.push_str(&format!("</{name}>"));
+}
+
+fn decode_csv(text: &str) -> Option<JsonValue> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let headers = reader.headers().ok()?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.ok()?;
+        let row: Map<String, JsonValue> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, field)| (header.to_string(), field.into()))
+            .collect();
+        rows.push(JsonValue::Object(row));
+    }
+    Some(JsonValue::Array(rows))
+}
+
+/// Columns are the union of every row's keys, in sorted order; a row without
+/// a column gets an empty field.
+fn encode_csv(value: &JsonValue) -> CoreResult<String> {
+    let ser_err = |e: csv::Error| CoreError::SerializationError(e.to_string());
+    let JsonValue::Array(rows) = value else {
+        return Err(CoreError::SerializationError(
+            "CSV rows must be an array".to_string(),
+        ));
+    };
+    let mut columns = std::collections::BTreeSet::new();
+    for row in rows {
+        let JsonValue::Object(row) = row else {
+            return Err(CoreError::SerializationError(
+                "CSV rows must be objects".to_string(),
+            ));
+        };
+        columns.extend(row.keys().map(String::as_str));
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns).map_err(ser_err)?;
+    for row in rows {
+        writer
+            .write_record(
+                columns
+                    .iter()
+                    .map(|column| row.get(*column).map(field_text).unwrap_or_default()),
+            )
+            .map_err(ser_err)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+fn decode_multipart(text: &str) -> Option<(JsonValue, String)> {
+    let boundary = text
+        .lines()
+        .find(|line| !line.trim().is_empty())?
+        .trim_end()
+        .strip_prefix("--")?
+        .to_string();
+    if boundary.is_empty() {
+        return None;
+    }
+    let delimiter = format!("--{boundary}");
+    let mut map = Map::new();
+    let mut closed = false;
+    for part in text.split(&delimiter).skip(1) {
+        if part.starts_with("--") {
+            closed = true;
+            break;
+        }
+        let part = part
+            .strip_prefix("\r\n")
+            .or_else(|| part.strip_prefix('\n'))?;
+        let part = part
+            .strip_suffix("\r\n")
+            .or_else(|| part.strip_suffix('\n'))
+            .unwrap_or(part);
+        let (head, content) = part
+            .split_once("\r\n\r\n")
+            .or_else(|| part.split_once("\n\n"))?;
+        let mut name = None;
+        let mut filename = None;
+        let mut content_type = None;
+        for header in head.lines() {
+            let Some((key, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if key.trim().eq_ignore_ascii_case("content-disposition") {
+                name = disposition_param(value, "name");
+                filename = disposition_param(value, "filename");
+            } else if key.trim().eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.to_string());
+            }
+        }
+        let value = if filename.is_none() && content_type.is_none() {
+            JsonValue::String(content.to_string())
+        } else {
+            let mut file = Map::new();
+            if let Some(filename) = filename {
+                file.insert("filename".to_string(), filename.into());
+            }
+            if let Some(content_type) = content_type {
+                file.insert("content_type".to_string(), content_type.into());
+            }
+            file.insert("content".to_string(), content.into());
+            JsonValue::Object(file)
+        };
+        insert_repeated(&mut map, name?, value);
+    }
+    closed.then_some((JsonValue::Object(map), boundary))
+}
+
+/// Value of `param` in a `Content-Disposition` header, quoted or not.
+fn disposition_param(header: &str, param: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn encode_multipart(value: &JsonValue, boundary: &str) -> CoreResult<String> {
+    let mut out = String::new();
+    for (name, field) in repeated_fields(value)? {
+        out.push_str(&format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\""
+        ));
+        let content = match field {
+            JsonValue::Object(file) if file.contains_key("content") => {
+                if let Some(filename) = file.get("filename") {
+                    out.push_str(&format!("; filename=\"{}\"", field_text(filename)));
+                }
+                out.push_str("\r\n");
+                if let Some(content_type) = file.get("content_type") {
+                    out.push_str(&format!("Content-Type: {}\r\n", field_text(content_type)));
+                }
+                field_text(&file["content"])
+            }
+            other => {
+                out.push_str("\r\n");
+                field_text(other)
+            }
+        };
+        out.push_str(&format!("\r\n{content}\r\n"));
+    }
+    out.push_str(&format!("--{boundary}--\r\n"));
+    Ok(out)
+}
+
+/// `serde_yaml_ng` cannot serialize arbitrary-precision numbers, so numbers
+/// are converted to the closest YAML number first.
+fn to_yaml(value: &JsonValue) -> serde_yaml_ng::Value {
+    use serde_yaml_ng::Value as YamlValue;
+    match value {
+        JsonValue::Null => YamlValue::Null,
+        JsonValue::Bool(b) => YamlValue::Bool(*b),
+        JsonValue::Number(n) => YamlValue::Number(if let Some(i) = n.as_i64() {
+            i.into()
+        } else if let Some(u) = n.as_u64() {
+            u.into()
+        } else {
+            n.as_f64().unwrap_or(f64::NAN).into()
+        }),
+        JsonValue::String(s) => YamlValue::String(s.clone()),
+        JsonValue::Array(values) => YamlValue::Sequence(values.iter().map(to_yaml).collect()),
+        JsonValue::Object(map) => YamlValue::Mapping(
+            map.iter()
+                .map(|(k, v)| (YamlValue::String(k.clone()), to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn maps_each_type_to_json() {
+        let cases = [
+            (
+                5,
+                r#"<?xml version="1.0"?><!-- c --><order id="7"><item>a &amp; b</item><item><![CDATA[<c>]]></item><note/><total currency="EUR">9.5</total></order>"#,
+                json!({"order": {
+                    "@id": "7",
+                    "item": ["a & b", "<c>"],
+                    "note": "",
+                    "total": {"@currency": "EUR", "#text": "9.5"}
+                }}),
+            ),
+            (
+                6,
+                "name: drift\ntags: [a, b]\nport: 8080\n",
+                json!({"name": "drift", "tags": ["a", "b"], "port": 8080}),
+            ),
+            (
+                8,
+                "id,name\n1,\"Doe, J\"\n2,x\n",
+                json!([{"id": "1", "name": "Doe, J"}, {"id": "2", "name": "x"}]),
+            ),
+            (
+                12,
+                "a=1&b=x+y%21&a=3",
+                json!({"a": ["1", "3"], "b": "x y!"}),
+            ),
+            (
+                13,
+                "--XyZ\r\nContent-Disposition: form-data; name=\"field\"\r\n\r\nvalue\r\n--XyZ\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nhello\r\n--XyZ--\r\n",
+                json!({
+                    "field": "value",
+                    "upload": {"filename": "a.txt", "content_type": "text/plain", "content": "hello"}
+                }),
+            ),
+        ];
+        for (code, text, expected) in cases {
+            let (value, decoded_type) = DecodedType::decode(code, text, NumberParsing::Baseline)
+                .expect("text should decode");
+            assert_eq!(value, expected, "{code}");
+
+            let encoded = decoded_type
+                .encode(&value)
+                .expect("encoding should succeed");
+            let (round_trip, _) = DecodedType::decode(code, &encoded, NumberParsing::Baseline)
+                .expect("encoded text should decode");
+            assert_eq!(round_trip, expected, "{code}: {encoded}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        assert_eq!(
+            DecodedType::decode(5, "<a><b></a>", NumberParsing::Baseline),
+            None
+        );
+        assert_eq!(
+            DecodedType::decode(5, "<a/><b/>", NumberParsing::Baseline),
+            None
+        );
+        assert_eq!(
+            DecodedType::decode(8, "a,b\n1\n", NumberParsing::Baseline),
+            None
+        );
+        assert_eq!(
+            DecodedType::decode(13, "--b\r\nno headers", NumberParsing::Baseline),
+            None
+        );
+        assert_eq!(
+            DecodedType::decode(6, "a: [1", NumberParsing::Baseline),
+            None
+        );
+        let deep = format!("{}1{}", "<a>".repeat(100_000), "</a>".repeat(100_000));
+        assert_eq!(DecodedType::decode(5, &deep, NumberParsing::Baseline), None);
+        assert_eq!(
+            DecodedType::decode(2, "<html/>", NumberParsing::Baseline),
+            None
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use base64::Engine as _;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use flate2::Compression;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use std::io::{Read, Write};
+
+use crate::error::{CoreError, CoreResult};
+
+/// `MergeRule.encoding` codes. `1` is `ENCODING_TYPE_BASE64` from the drift
+/// schemas; the rest are defined by the core from 1001 up, clear of the
+/// schemas' `EncodingType` range, so a code the schemas add later is never
+/// read as one of ours and an unknown code leaves its member raw. Compressed
+/// encodings expect standard base64 of the compressed bytes, which is how
+/// SDKs record binary bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Base64 = 1,
+    /// URL-safe alphabet, with or without `=` padding.
+    Base64Url = 1001,
+    Hex = 1002,
+    /// URL percent-encoding.
+    Percent = 1003,
+    Gzip = 1004,
+    /// zlib-wrapped deflate, as sent with `Content-Encoding: deflate`; raw
+    /// deflate streams are accepted too.
+    Deflate = 1005,
+    Brotli = 1006,
+    Zstd = 1007,
+}
+
+const BASE64_URL: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decompressed size cap for members when the payload limits set no
+/// `max_bytes`, so an unlimited call still cannot be made to inflate a few
+/// kilobytes of input into gigabytes.
+pub(crate) const MAX_DECOMPRESSED_BYTES: usize = 64 << 20;
+
+/// Everything but RFC 3986 unreserved characters.
+const PERCENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+impl Encoding {
+    pub(crate) fn from_code(code: i32) -> Option<Self> {
+        Some(match code {
+            1 => Self::Base64,
+            1001 => Self::Base64Url,
+            1002 => Self::Hex,
+            1003 => Self::Percent,
+            1004 => Self::Gzip,
+            1005 => Self::Deflate,
+            1006 => Self::Brotli,
+            1007 => Self::Zstd,
+            _ => return None,
+        })
+    }
+
+    /// Decoded text of `s`, or `None` when `s` is not valid in this encoding
+    /// or decompresses to more than `max_len` bytes ([`MAX_DECOMPRESSED_BYTES`]
+    /// when unset).
+    pub(crate) fn decode(self, s: &str, max_len: Option<usize>) -> Option<String> {
+        let bytes = match self {
+            Self::Base64 => STANDARD.decode(s).ok()?,
+            Self::Base64Url => BASE64_URL.decode(s).ok()?,
+            Self::Hex => decode_hex(s)?,
+            Self::Percent => return Some(percent_decode_str(s).decode_utf8_lossy().into_owned()),
+            Self::Gzip => read_capped(GzDecoder::new(&STANDARD.decode(s).ok()?[..]), max_len)?,
+            Self::Deflate => {
+                let compressed = STANDARD.decode(s).ok()?;
+                read_capped(ZlibDecoder::new(&compressed[..]), max_len)
+                    .or_else(|| read_capped(DeflateDecoder::new(&compressed[..]), max_len))?
+            }
+            Self::Brotli => read_capped(
+                brotli::Decompressor::new(&STANDARD.decode(s).ok()?[..], 4096),
+                max_len,
+            )?,
+            Self::Zstd => {
+                let compressed = STANDARD.decode(s).ok()?;
+                let decoder = ruzstd::decoding::StreamingDecoder::new(&compressed[..]).ok()?;
+                read_capped(decoder, max_len)?
+            }
+        };
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Inverse of [`Encoding::decode`]. Compressed output need not match the
+    /// original bytes, only decode back to `text`.
+    pub(crate) fn encode(self, text: &str) -> CoreResult<String> {
+        let io_err = |e: std::io::Error| CoreError::SerializationError(e.to_string());
+        let bytes = text.as_bytes();
+        Ok(match self {
+            Self::Base64 => STANDARD.encode(bytes),
+            Self::Base64Url => BASE64_URL.encode(bytes),
+            Self::Hex => bytes.iter().map(|b| format!("{b:02x}")).collect(),
+            Self::Percent => utf8_percent_encode(text, PERCENT_ENCODE_SET).to_string(),
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(bytes).map_err(io_err)?;
+                STANDARD.encode(encoder.finish().map_err(io_err)?)
+            }
+            Self::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(bytes).map_err(io_err)?;
+                STANDARD.encode(encoder.finish().map_err(io_err)?)
+            }
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                encoder.write_all(bytes).map_err(io_err)?;
+                STANDARD.encode(encoder.into_inner())
+            }
+            Self::Zstd => STANDARD.encode(ruzstd::encoding::compress_to_vec(
+                bytes,
+                ruzstd::encoding::CompressionLevel::Fastest,
+            )),
+        })
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Reads `reader` to the end unless it yields more than `max_len` bytes,
+/// which keeps decompression bombs out of the decoded value.
+fn read_capped(reader: impl Read, max_len: Option<usize>) -> Option<Vec<u8>> {
+    let max = max_len.unwrap_or(MAX_DECOMPRESSED_BYTES);
+    let mut out = Vec::new();
+    reader.take(max as u64 + 1).read_to_end(&mut out).ok()?;
+    (out.len() <= max).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_encoding_round_trips() {
+        let long = "x".repeat(70_000);
+        for text in [r#"{"msg":"héllo wörld/?&=","n":[1,2,3]}"#, &long] {
+            for code in [1].into_iter().chain(1001..=1007) {
+                let encoding = Encoding::from_code(code).expect("code should be known");
+                let encoded = encoding.encode(text).expect("encoding should succeed");
+                assert_eq!(
+                    encoding.decode(&encoded, None).as_deref(),
+                    Some(text),
+                    "{encoding:?}"
+                );
+            }
+        }
+        for unknown in [0, 2, 3, 8, 1000, 1008] {
+            assert_eq!(Encoding::from_code(unknown), None);
+        }
+    }
+
+    #[test]
+    fn decodes_padded_base64url_raw_deflate_and_caps_decompression() {
+        assert_eq!(
+            Encoding::Base64Url.decode("Pz8_Pw==", None).as_deref(),
+            Some("????")
+        );
+        assert_eq!(Encoding::Hex.decode("6a7", None), None);
+
+        let mut raw = flate2::write::DeflateEncoder::new(Vec::new(), Compression::default());
+        raw.write_all(b"plain deflate")
+            .expect("write should succeed");
+        let raw = STANDARD.encode(raw.finish().expect("finish should succeed"));
+        assert_eq!(
+            Encoding::Deflate.decode(&raw, None).as_deref(),
+            Some("plain deflate")
+        );
+
+        let bomb = Encoding::Gzip
+            .encode(&"a".repeat(10_000))
+            .expect("encoding should succeed");
+        assert_eq!(Encoding::Gzip.decode(&bomb, Some(100)), None);
+
+        let at_cap = std::io::repeat(b'a').take(MAX_DECOMPRESSED_BYTES as u64);
+        assert!(read_capped(at_cap, None).is_some());
+        let over_cap = std::io::repeat(b'a').take(MAX_DECOMPRESSED_BYTES as u64 + 1);
+        assert_eq!(read_capped(over_cap, None), None);
+    }
+}
@@ -4,6 +4,9 @@ use std::fmt::{Display, Formatter};
 pub enum CoreError {
     InvalidJson(String),
     SerializationError(String),
+    LimitExceeded(String),
+    /// Options that cannot be honoured together.
+    InvalidOptions(String),
 }
 
 impl Display for CoreError {
@@ -11,6 +14,8 @@ impl Display for CoreError {
         match self {
             CoreError::InvalidJson(e) => write!(f, "invalid json: {e}"),
             CoreError::SerializationError(e) => write!(f, "serialization error: {e}"),
+            CoreError::LimitExceeded(e) => write!(f, "limit exceeded: {e}"),
+            CoreError::InvalidOptions(e) => write!(f, "invalid options: {e}"),
         }
     }
 }
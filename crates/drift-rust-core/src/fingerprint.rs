@@ -0,0 +1,110 @@
+use serde_json::Value as JsonValue;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::error::{CoreError, CoreResult};
+use crate::numbers::lossless_literal;
+use crate::path::{push_index, push_key};
+
+const PREFIX: &str = "simhash:";
+
+/// 64-bit SimHash over the leaves of `value`. Every leaf contributes its path
+/// and its `path=value` pair, so payloads with the same shape stay close even
+/// when most values differ. Rendered as `simhash:` + 16 hex digits.
+pub(crate) fn simhash_fingerprint(value: &JsonValue) -> String {
+    let mut weights = [0i64; 64];
+    let mut path = String::new();
+    collect_leaf_features(value, &mut path, &mut |feature| {
+        let h = xxh3_64(feature);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if h >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    });
+    let fingerprint = weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |acc, (bit, _)| acc | 1 << bit);
+    format!("{PREFIX}{fingerprint:016x}")
+}
+
+fn collect_leaf_features(value: &JsonValue, path: &mut String, add: &mut dyn FnMut(&[u8])) {
+    match value {
+        JsonValue::Array(arr) if !arr.is_empty() => {
+            for (i, child) in arr.iter().enumerate() {
+                let len = path.len();
+                push_index(path, i);
+                collect_leaf_features(child, path, add);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Object(map) if !map.is_empty() && lossless_literal(value).is_none() => {
+            for (k, child) in map {
+                let len = path.len();
+                push_key(path, k);
+                collect_leaf_features(child, path, add);
+                path.truncate(len);
+            }
+        }
+        leaf => {
+            add(path.as_bytes());
+            let feature = match lossless_literal(leaf) {
+                Some(literal) => format!("{path}={literal}"),
+                None => format!("{path}={leaf}"),
+            };
+            add(feature.as_bytes());
+        }
+    }
+}
+
+/// Estimated similarity of two fingerprints from [`simhash_fingerprint`]:
+/// the share of equal bits, from `0.0` to `1.0` (identical).
+pub fn fingerprint_similarity(a: &str, b: &str) -> CoreResult<f64> {
+    let distance = (parse_fingerprint(a)? ^ parse_fingerprint(b)?).count_ones();
+    Ok(1.0 - f64::from(distance) / 64.0)
+}
+
+fn parse_fingerprint(fingerprint: &str) -> CoreResult<u64> {
+    fingerprint
+        .strip_prefix(PREFIX)
+        .filter(|hex| hex.len() == 16)
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| CoreError::InvalidJson(format!("invalid fingerprint: {fingerprint}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_payloads_score_higher_than_unrelated_ones() {
+        let base = serde_json::json!({
+            "method": "GET",
+            "path": "/users/1",
+            "headers": {"accept": "application/json", "host": "api"},
+            "query": {"page": 1, "limit": 20}
+        });
+        let mut close = base.clone();
+        close["query"]["page"] = serde_json::json!(2);
+        let unrelated = serde_json::json!({"event": "signup", "plan": ["pro", "monthly"]});
+
+        let base_fp = simhash_fingerprint(&base);
+        let near = fingerprint_similarity(&base_fp, &simhash_fingerprint(&close))
+            .expect("fingerprints should parse");
+        let far = fingerprint_similarity(&base_fp, &simhash_fingerprint(&unrelated))
+            .expect("fingerprints should parse");
+
+        assert_eq!(
+            fingerprint_similarity(&base_fp, &base_fp).expect("fingerprint should parse"),
+            1.0
+        );
+        assert!(near > far, "near {near} should beat far {far}");
+        assert!(near >= 0.75);
+    }
+
+    #[test]
+    fn malformed_fingerprints_are_rejected() {
+        let err = fingerprint_similarity("simhash:zz", "simhash:0000000000000000")
+            .expect_err("malformed fingerprint should fail");
+        assert!(matches!(err, CoreError::InvalidJson(_)));
+    }
+}
@@ -1,54 +1,407 @@
+use serde::Deserialize;
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::io::Write;
+use xxhash_rust::xxh3::Xxh3;
 
 use crate::error::{CoreError, CoreResult};
-use crate::normalize::{json_roundtrip_normalize, parse_json};
+use crate::jcs::{write_jcs, write_jcs_string};
+use crate::normalize::{NormalizeOptions, NumberParsing, normalize_value, parse_payload};
+use crate::numbers::{lossless_literal, to_json_string};
+use crate::path::{push_index, push_key};
+
+/// Byte form of the value that is fed into the digest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanonicalForm {
+    /// serde_json compact output with object keys sorted by byte order.
+    #[default]
+    Sorted,
+    /// RFC 8785 JSON Canonicalization Scheme.
+    Jcs,
+}
+
+/// Digest used for value and schema hashes. Non-default algorithms tag their
+/// hex digest with a prefix so hashes from different algorithms never compare
+/// equal by accident; SHA-256 stays unprefixed for compatibility.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    /// `b3:` + 256-bit BLAKE3.
+    Blake3,
+    /// `xxh3:` + 128-bit XXH3. Not cryptographic; only for matching.
+    Xxh3,
+}
+
+/// Versioned set of hashing rules. Recordings keep the scheme their hashes
+/// were computed with so replay can recompute them that way even after the
+/// default moves on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashScheme {
+    /// The original rules, pinned: default normalization with numbers read
+    /// like plain serde_json, sorted compact bytes, SHA-256 and array `items`
+    /// schemas from the first element. Options that would change any of
+    /// these are rejected with [`CoreError::InvalidOptions`]. Default options
+    /// hash the same way.
+    #[default]
+    V1,
+    /// Canonical numbers read as the correctly rounded f64 and RFC 8785 (JCS)
+    /// bytes, on top of the other options.
+    V2,
+}
+
+impl HashScheme {
+    /// Every scheme this build can compute, oldest first.
+    pub const ALL: &'static [HashScheme] = &[HashScheme::V1, HashScheme::V2];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashScheme::V1 => "v1",
+            HashScheme::V2 => "v2",
+        }
+    }
+}
+
+/// What a hash is computed with once an optional scheme has been applied to
+/// the caller's options.
+pub(crate) struct HashRules<'a> {
+    /// The scheme these rules are, also when none was asked for; `None` for
+    /// options no scheme pins.
+    pub(crate) scheme: Option<HashScheme>,
+    pub(crate) normalize: Cow<'a, NormalizeOptions>,
+    pub(crate) form: CanonicalForm,
+    pub(crate) algorithm: HashAlgorithm,
+    pub(crate) numbers: NumberParsing,
+    /// Merge every array element into `items` schemas instead of describing
+    /// the first one; only export payload schemas have arrays to merge.
+    pub(crate) union_items: bool,
+}
+
+impl<'a> HashRules<'a> {
+    /// The options as given without a scheme, else what `scheme` pins.
+    /// `v1` fails on options it would have to ignore.
+    pub(crate) fn new(
+        scheme: Option<HashScheme>,
+        normalize: &'a NormalizeOptions,
+        form: CanonicalForm,
+        algorithm: HashAlgorithm,
+        union_items: bool,
+    ) -> CoreResult<Self> {
+        let is_v1 = *normalize == NormalizeOptions::default()
+            && form == CanonicalForm::Sorted
+            && algorithm == HashAlgorithm::Sha256
+            && !union_items;
+        let mut rules = match scheme {
+            Some(HashScheme::V1) if !is_v1 => {
+                return Err(CoreError::InvalidOptions(
+                    "hash scheme v1 pins default normalization, sorted form, sha256 and first-element items".to_string(),
+                ));
+            }
+            None => Self {
+                scheme: is_v1.then_some(HashScheme::V1),
+                normalize: Cow::Borrowed(normalize),
+                form,
+                algorithm,
+                numbers: NumberParsing::Baseline,
+                union_items,
+            },
+            Some(HashScheme::V1) => Self {
+                scheme,
+                normalize: Cow::Borrowed(normalize),
+                form: CanonicalForm::Sorted,
+                algorithm: HashAlgorithm::Sha256,
+                numbers: NumberParsing::Baseline,
+                union_items: false,
+            },
+            Some(HashScheme::V2) => {
+                let mut normalize = normalize.clone();
+                normalize.canonical_numbers = true;
+                Self {
+                    scheme,
+                    normalize: Cow::Owned(normalize),
+                    form: CanonicalForm::Jcs,
+                    algorithm,
+                    numbers: NumberParsing::Exact,
+                    union_items,
+                }
+            }
+        };
+        rules.numbers = rules.numbers.or_lossless(&rules.normalize);
+        Ok(rules)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HashOptions {
+    pub normalize: NormalizeOptions,
+    pub canonical_form: CanonicalForm,
+    pub algorithm: HashAlgorithm,
+    /// Pins the rules above to a scheme; unset uses them as given. `v1` only
+    /// accepts their defaults.
+    pub scheme: Option<HashScheme>,
+}
+
+impl HashOptions {
+    pub fn from_json(options_json: &str) -> CoreResult<Self> {
+        serde_json::from_str(options_json).map_err(|e| CoreError::InvalidJson(e.to_string()))
+    }
+}
 
 pub fn deterministic_hash(payload_json: &str) -> CoreResult<String> {
-    let (_, hash) = normalize_and_hash(payload_json)?;
+    deterministic_hash_with_options(payload_json, &HashOptions::default())
+}
+
+pub fn deterministic_hash_with_options(
+    payload_json: &str,
+    options: &HashOptions,
+) -> CoreResult<String> {
+    let (_, hash) = normalize_and_hash_with_options(payload_json, options)?;
     Ok(hash)
 }
 
 pub fn normalize_and_hash(payload_json: &str) -> CoreResult<(String, String)> {
-    let input = parse_json(payload_json)?;
-    let normalized_value = json_roundtrip_normalize(&input)?;
-    let normalized_json = serde_json::to_string(&normalized_value)
-        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
-    let hash = hash_json_value_deterministic(&normalized_value)?;
+    normalize_and_hash_with_options(payload_json, &HashOptions::default())
+}
+
+pub fn normalize_and_hash_with_options(
+    payload_json: &str,
+    options: &HashOptions,
+) -> CoreResult<(String, String)> {
+    let rules = HashRules::new(
+        options.scheme,
+        &options.normalize,
+        options.canonical_form,
+        options.algorithm,
+        false,
+    )?;
+    let input = parse_payload(payload_json, rules.numbers)?;
+    let normalized_value = normalize_value(input, &rules.normalize, rules.numbers)?;
+    let normalized_json = to_json_string(&normalized_value)?;
+    let hash = hash_json_value(&normalized_value, rules.form, rules.algorithm)?;
     Ok((normalized_json, hash))
 }
 
 pub(crate) fn hash_json_value_deterministic(v: &JsonValue) -> CoreResult<String> {
-    let sorted = sort_keys_recursively(v);
-    let compact =
-        serde_json::to_string(&sorted).map_err(|e| CoreError::SerializationError(e.to_string()))?;
-    let mut hasher = Sha256::new();
-    hasher.update(compact.as_bytes());
-    Ok(format!("{:x}", hasher.finalize()))
+    hash_json_value(v, CanonicalForm::Sorted, HashAlgorithm::Sha256)
+}
+
+pub(crate) fn hash_json_value(
+    v: &JsonValue,
+    form: CanonicalForm,
+    algorithm: HashAlgorithm,
+) -> CoreResult<String> {
+    let mut hasher = CanonicalHasher::new(algorithm);
+    write_canonical(v, form, &mut hasher)?;
+    Ok(hasher.finish())
 }
 
-fn sort_keys_recursively(v: &JsonValue) -> JsonValue {
+/// Streams the canonical bytes of `v` into `out` without building a sorted
+/// copy of the tree or an intermediate string.
+pub(crate) fn write_canonical<W: Write>(
+    v: &JsonValue,
+    form: CanonicalForm,
+    out: &mut W,
+) -> CoreResult<()> {
+    write_canonical_with(v, form, out, &mut |_, child, out| {
+        write_canonical(child, form, out)
+    })
+}
+
+/// `io::Write` sink that feeds every write into the selected digest.
+pub(crate) enum CanonicalHasher {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+    Xxh3(Box<Xxh3>),
+}
+
+impl CanonicalHasher {
+    pub(crate) fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::new(blake3::Hasher::new())),
+            HashAlgorithm::Xxh3 => Self::Xxh3(Box::new(Xxh3::new())),
+        }
+    }
+
+    /// Algorithm-tagged hex digest.
+    pub(crate) fn finish(self) -> String {
+        match self {
+            Self::Sha256(h) => format!("{:x}", h.finalize()),
+            Self::Blake3(h) => format!("b3:{}", h.finalize().to_hex()),
+            Self::Xxh3(h) => format!("xxh3:{:032x}", h.digest128()),
+        }
+    }
+}
+
+impl Write for CanonicalHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Sha256(h) => h.update(buf),
+            Self::Blake3(h) => {
+                h.update(buf);
+            }
+            Self::Xxh3(h) => h.update(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+enum ChildKey<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Writes the punctuation, keys and scalars of `v` in `form` and hands each
+/// child to `write_child`: the one ordered walk behind both forms. Sorted
+/// output is the same bytes as compact `serde_json` with object keys sorted
+/// by byte order; JCS orders keys by UTF-16 code units and writes scalars
+/// with [`write_jcs`]. Lossless numbers are written as their literal, which
+/// JCS cannot represent.
+fn write_canonical_with<W: Write>(
+    v: &JsonValue,
+    form: CanonicalForm,
+    out: &mut W,
+    write_child: &mut dyn FnMut(ChildKey<'_>, &JsonValue, &mut W) -> CoreResult<()>,
+) -> CoreResult<()> {
+    let io_err = |e: std::io::Error| CoreError::SerializationError(e.to_string());
+    if let Some(literal) = lossless_literal(v) {
+        return match form {
+            CanonicalForm::Sorted => out.write_all(literal.as_bytes()).map_err(io_err),
+            CanonicalForm::Jcs => Err(CoreError::SerializationError(format!(
+                "number {literal} is not representable in JCS"
+            ))),
+        };
+    }
     match v {
+        JsonValue::Array(arr) => {
+            out.write_all(b"[").map_err(io_err)?;
+            for (i, child) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b",").map_err(io_err)?;
+                }
+                write_child(ChildKey::Index(i), child, out)?;
+            }
+            out.write_all(b"]").map_err(io_err)
+        }
         JsonValue::Object(map) => {
-            let mut sorted = BTreeMap::new();
-            for (k, child) in map {
-                sorted.insert(k.clone(), sort_keys_recursively(child));
+            // serde_json maps iterate in key order unless `preserve_order` is
+            // enabled somewhere in the dependency graph; sort references so
+            // the hash never depends on that.
+            let mut entries: Vec<(&String, &JsonValue)> = map.iter().collect();
+            match form {
+                CanonicalForm::Sorted => entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b)),
+                CanonicalForm::Jcs => entries
+                    .sort_unstable_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16())),
             }
-            let mut out = serde_json::Map::new();
-            for (k, child) in sorted {
-                out.insert(k, child);
+            out.write_all(b"{").map_err(io_err)?;
+            for (i, (k, child)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b",").map_err(io_err)?;
+                }
+                match form {
+                    CanonicalForm::Sorted => write_scalar(k, out)?,
+                    CanonicalForm::Jcs => write_jcs_string(k, out)?,
+                }
+                out.write_all(b":").map_err(io_err)?;
+                write_child(ChildKey::Key(k), child, out)?;
             }
-            JsonValue::Object(out)
+            out.write_all(b"}").map_err(io_err)
+        }
+        scalar => match form {
+            CanonicalForm::Sorted => write_scalar(scalar, out),
+            CanonicalForm::Jcs => write_jcs(scalar, out),
+        },
+    }
+}
+
+/// Hashes `v` and, in the same pass, every subtree down to `depth` levels
+/// below the root, keyed by rendered path (`body`, `headers.host`,
+/// `items[0]`). Each subtree hash equals hashing that subtree on its own.
+pub(crate) fn hash_json_value_with_paths(
+    v: &JsonValue,
+    form: CanonicalForm,
+    algorithm: HashAlgorithm,
+    depth: usize,
+) -> CoreResult<(String, BTreeMap<String, String>)> {
+    let mut out = PathHashWriter {
+        form,
+        algorithm,
+        root: CanonicalHasher::new(algorithm),
+        open: Vec::new(),
+    };
+    let mut hashes = BTreeMap::new();
+    write_with_paths(v, &mut out, &mut String::new(), depth, &mut hashes)?;
+    Ok((out.root.finish(), hashes))
+}
+
+/// Fans canonical bytes out to the whole-value hasher and to the hasher of
+/// every subtree currently being written.
+struct PathHashWriter {
+    form: CanonicalForm,
+    algorithm: HashAlgorithm,
+    root: CanonicalHasher,
+    open: Vec<CanonicalHasher>,
+}
+
+impl Write for PathHashWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.root.write_all(buf)?;
+        for hasher in &mut self.open {
+            hasher.write_all(buf)?;
         }
-        JsonValue::Array(arr) => JsonValue::Array(arr.iter().map(sort_keys_recursively).collect()),
-        _ => v.clone(),
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
+fn write_with_paths(
+    v: &JsonValue,
+    out: &mut PathHashWriter,
+    path: &mut String,
+    remaining_depth: usize,
+    hashes: &mut BTreeMap<String, String>,
+) -> CoreResult<()> {
+    if remaining_depth == 0 {
+        return write_canonical(v, out.form, out);
+    }
+    write_canonical_with(v, out.form, out, &mut |key, child, out| {
+        let len = path.len();
+        match key {
+            ChildKey::Key(k) => push_key(path, k),
+            ChildKey::Index(i) => push_index(path, i),
+        }
+        out.open.push(CanonicalHasher::new(out.algorithm));
+        write_with_paths(child, out, path, remaining_depth - 1, hashes)?;
+        if let Some(hasher) = out.open.pop() {
+            hashes.insert(path.clone(), hasher.finish());
+        }
+        path.truncate(len);
+        Ok(())
+    })
+}
+
+fn write_scalar<W: Write, T: serde::Serialize + ?Sized>(v: &T, out: &mut W) -> CoreResult<()> {
+    serde_json::to_writer(out, v).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::payload::{process_export_payload, process_export_payload_with_options};
+    use crate::types::ExportPayloadOptions;
 
     #[test]
     fn deterministic_hash_is_stable_across_object_key_order() {
@@ -61,6 +414,58 @@ mod tests {
         assert_eq!(hash_a, hash_b);
     }
 
+    #[test]
+    fn streamed_sorted_bytes_match_compact_serde_json_output() {
+        let value: JsonValue =
+            serde_json::from_str(r#"{"b":[1,2.5,"x\"y"],"a":{"d":null,"c":true},"":{}}"#)
+                .expect("value should parse");
+        let mut streamed = Vec::new();
+        write_canonical(&value, CanonicalForm::Sorted, &mut streamed)
+            .expect("writing should succeed");
+
+        assert_eq!(
+            String::from_utf8(streamed).expect("output is utf-8"),
+            serde_json::to_string(&value).expect("value should serialize")
+        );
+        assert_eq!(
+            hash_json_value_deterministic(&value).expect("hash should succeed"),
+            format!(
+                "{:x}",
+                Sha256::digest(br#"{"":{},"a":{"c":true,"d":null},"b":[1,2.5,"x\"y"]}"#)
+            )
+        );
+    }
+
+    #[test]
+    fn path_hashes_match_hashing_each_subtree_on_its_own() {
+        let value = serde_json::json!({
+            "headers": {"host": "a", "x.id": "1"},
+            "body": {"items": [{"n": 1}, {"n": 2}]}
+        });
+        let (root, paths) =
+            hash_json_value_with_paths(&value, CanonicalForm::Sorted, HashAlgorithm::Sha256, 2)
+                .expect("hashing should succeed");
+
+        assert_eq!(
+            root,
+            hash_json_value_deterministic(&value).expect("hash should succeed")
+        );
+        assert_eq!(
+            paths.keys().collect::<Vec<_>>(),
+            [
+                "body",
+                "body.items",
+                "headers",
+                "headers.host",
+                "headers[\"x.id\"]"
+            ]
+        );
+        assert_eq!(
+            paths["body.items"],
+            hash_json_value_deterministic(&value["body"]["items"]).expect("hash should succeed")
+        );
+    }
+
     #[test]
     fn normalize_and_hash_returns_compact_json_and_sha256_hex() {
         let input = "{ \"k\": \"v\", \"n\": 3 }";
@@ -70,9 +475,255 @@ mod tests {
         assert_eq!(hash.len(), 64);
     }
 
+    #[test]
+    fn deterministic_hash_with_options_ignores_dropped_nulls() {
+        let options = HashOptions {
+            normalize: NormalizeOptions {
+                drop_nulls: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let with_null = deterministic_hash_with_options(r#"{"a":1,"b":null}"#, &options)
+            .expect("hash should succeed");
+        let without_null = deterministic_hash(r#"{"a":1}"#).expect("hash should succeed");
+
+        assert_eq!(with_null, without_null);
+    }
+
+    #[test]
+    fn jcs_hash_matches_sha256_of_rfc8785_output() {
+        let options =
+            HashOptions::from_json(r#"{"canonical_form":"jcs"}"#).expect("options should parse");
+        let hash = deterministic_hash_with_options(r#"{"b":1.0,"a":[1e21,"x"]}"#, &options)
+            .expect("hash should succeed");
+
+        let mut hasher = Sha256::new();
+        hasher.update(br#"{"a":[1e+21,"x"],"b":1}"#);
+        assert_eq!(hash, format!("{:x}", hasher.finalize()));
+    }
+
+    #[test]
+    fn v1_scheme_pins_the_baseline_hashes() {
+        // Hashes computed before hash schemes existed.
+        let golden = [
+            (
+                r#"{"a":-0}"#,
+                "952b7dc455870c265da6a6fb15ef60891abf562f0e3327f095914610b3d00811",
+            ),
+            (
+                r#"{"big":9223372036854775808,"neg":-9223372036854775809,"u":18446744073709551615,"huge":123456789012345678901234567890}"#,
+                "7d3afe4529d331ec996ebd64dde33594fe208d9223701fbdd1bee4f4b7b1c98c",
+            ),
+            (
+                r#"{"a":[1,"a",null]}"#,
+                "7b9608e9b44888f24d2bd223bfe4558e86469efee21bcf86c8a63ab9bcf47934",
+            ),
+            (
+                "[2.2250738585072011e-308,4.9e-324,2.4703282292062328e-324,0.30000000000000004441,1.00000000000000011102230246251565404236316680908203125,9007199254740993,1e-400,-0.0,-0e5,123456789012345678901234567890e-10]",
+                "abce6a31897cf9f928bbf51c4917b415d1c35ae4ea173cbf420f8d3b53a6f7b5",
+            ),
+        ];
+        let pinned = HashOptions::from_json(r#"{"scheme":"v1"}"#).expect("options should parse");
+        for (input, expected) in golden {
+            assert_eq!(
+                deterministic_hash(input).expect("hashing should succeed"),
+                expected
+            );
+            assert_eq!(
+                deterministic_hash_with_options(input, &pinned).expect("hashing should succeed"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn v1_scheme_rejects_options_it_cannot_honour() {
+        for options_json in [
+            r#"{"scheme":"v1","canonical_form":"jcs"}"#,
+            r#"{"scheme":"v1","algorithm":"blake3"}"#,
+            r#"{"scheme":"v1","normalize":{"lossless_numbers":true}}"#,
+        ] {
+            let options = HashOptions::from_json(options_json).expect("options should parse");
+            assert!(matches!(
+                normalize_and_hash_with_options("{}", &options),
+                Err(CoreError::InvalidOptions(_))
+            ));
+        }
+
+        let report = |options_json: &str| {
+            let options =
+                ExportPayloadOptions::from_json(options_json).expect("options should parse");
+            process_export_payload_with_options("{}", None, &options)
+                .map(|result| result.hash_scheme)
+        };
+        for options_json in [
+            r#"{"hash_scheme":"v1","hash_algorithm":"xxh3"}"#,
+            r#"{"hash_scheme":"v1","union_items":true}"#,
+        ] {
+            assert!(matches!(
+                report(options_json),
+                Err(CoreError::InvalidOptions(_))
+            ));
+        }
+        assert_eq!(report("{}").ok(), Some(Some(HashScheme::V1)));
+        assert_eq!(report(r#"{"hash_algorithm":"xxh3"}"#).ok(), Some(None));
+    }
+
+    #[test]
+    fn v2_scheme_hashes_canonical_numbers_as_jcs() {
+        let input = r#"{"\u00e9":1.0,"z":[2.50]}"#;
+        let hash_with = |options_json: &str| {
+            deterministic_hash_with_options(
+                input,
+                &HashOptions::from_json(options_json).expect("options should parse"),
+            )
+            .expect("hashing should succeed")
+        };
+
+        assert_eq!(
+            hash_with(r#"{"scheme":"v2"}"#),
+            hash_with(r#"{"canonical_form":"jcs","normalize":{"canonical_numbers":true}}"#)
+        );
+        assert_ne!(hash_with(r#"{"scheme":"v2"}"#), hash_with("{}"));
+        assert_eq!(HashScheme::default(), HashScheme::V1);
+        assert_eq!(
+            HashScheme::ALL
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>(),
+            ["v1", "v2"]
+        );
+    }
+
+    #[test]
+    fn alternative_algorithms_tag_their_digests() {
+        let input = r#"{"k":"v"}"#;
+        let hash_with = |algorithm: &str| {
+            let options = HashOptions::from_json(&format!(r#"{{"algorithm":"{algorithm}"}}"#))
+                .expect("options should parse");
+            deterministic_hash_with_options(input, &options).expect("hash should succeed")
+        };
+
+        assert_eq!(
+            hash_with("sha256"),
+            deterministic_hash(input).expect("hash should succeed")
+        );
+        assert_eq!(
+            hash_with("blake3"),
+            format!("b3:{}", blake3::hash(input.as_bytes()).to_hex())
+        );
+        assert_eq!(
+            hash_with("xxh3"),
+            format!(
+                "xxh3:{:032x}",
+                xxhash_rust::xxh3::xxh3_128(input.as_bytes())
+            )
+        );
+    }
+
     #[test]
     fn deterministic_hash_returns_invalid_json_error() {
         let err = deterministic_hash("nope").expect_err("invalid json should fail");
         assert!(matches!(err, CoreError::InvalidJson(_)));
     }
+
+    #[test]
+    fn v2_scheme_hashes_integral_floats_like_integers() {
+        let v2 = ExportPayloadOptions::from_json(r#"{"hash_scheme":"v2","path_hash_depth":1}"#)
+            .expect("options should parse");
+        let float = process_export_payload_with_options(r#"{"n":1.0}"#, None, &v2)
+            .expect("processing should work");
+        let int = process_export_payload_with_options(r#"{"n":1}"#, None, &v2)
+            .expect("processing should work");
+        let v1 = process_export_payload(r#"{"n":1.0}"#, None).expect("processing should work");
+
+        assert_eq!(float.hash_scheme, Some(crate::HashScheme::V2));
+        assert_eq!(v1.hash_scheme, Some(crate::HashScheme::V1));
+        assert_eq!(float.decoded_value_hash, int.decoded_value_hash);
+        assert_ne!(float.decoded_value_hash, v1.decoded_value_hash);
+        assert_eq!(float.path_hashes["n"], int.path_hashes["n"]);
+    }
+
+    #[test]
+    fn v1_scheme_pins_the_baseline_payload_hashes() {
+        // (payload, merges, decoded_value_hash, decoded_schema_hash) computed
+        // before hash schemes existed.
+        let golden = [
+            (
+                r#"{"a":-0}"#,
+                None,
+                "952b7dc455870c265da6a6fb15ef60891abf562f0e3327f095914610b3d00811",
+                "c55ab3b6e544e3b8a31cc19969c1d1ac0160ebc7cb202f27edafdb84a8dfff45",
+            ),
+            (
+                r#"{"big":9223372036854775808,"neg":-9223372036854775809,"u":18446744073709551615,"huge":123456789012345678901234567890}"#,
+                None,
+                "7d3afe4529d331ec996ebd64dde33594fe208d9223701fbdd1bee4f4b7b1c98c",
+                "7f08ec0698a849872b1c9146120b3d6238d6eb34b24b6b4f903afb24b316bcdd",
+            ),
+            (
+                r#"{"a":[1,"a",null]}"#,
+                None,
+                "7b9608e9b44888f24d2bd223bfe4558e86469efee21bcf86c8a63ab9bcf47934",
+                "62638f5fae91bda2e56e577ad35c16fe5515f7236c300986c7b84f5de94f30ca",
+            ),
+            (
+                r#"{"a":[]}"#,
+                None,
+                "50e8660084976a10f0b3b9b3a6352d5881cbd219b5587a26224971a60ff2cc55",
+                "7942c80d878efbfbae3e6efe39af86120cde849baec2a952091e797015a1dac4",
+            ),
+            (
+                // base64 of {"n":1.50,"z":-0,"l":[]}
+                r#"{"body":"eyJuIjoxLjUwLCJ6IjotMCwibCI6W119","ids":[7,"x"]}"#,
+                Some(r#"{"body":{"encoding":1,"decoded_type":1}}"#),
+                "901882ea0e4d95b985bea467913c367b71e8d186a1ef06435b4d073d57152e4b",
+                "f125640c9df197b49eeb95cfaf87807a9db3065fef8a5bf41ad86cc09046054d",
+            ),
+        ];
+        let pinned = ExportPayloadOptions::from_json(r#"{"hash_scheme":"v1"}"#)
+            .expect("options should parse");
+        for (payload, merges, value_hash, schema_hash) in golden {
+            for options in [&ExportPayloadOptions::default(), &pinned] {
+                let result = process_export_payload_with_options(payload, merges, options)
+                    .expect("processing should work");
+                assert_eq!(result.decoded_value_hash, value_hash);
+                assert_eq!(result.decoded_schema_hash, schema_hash);
+            }
+        }
+    }
+
+    #[test]
+    fn path_hashes_pinpoint_the_changed_subtree() {
+        let options = ExportPayloadOptions::from_json(r#"{"path_hash_depth":1}"#)
+            .expect("options should parse");
+        let first = process_export_payload_with_options(
+            r#"{"headers":{"host":"a"},"body":{"n":1}}"#,
+            None,
+            &options,
+        )
+        .expect("processing should work");
+        let replay = process_export_payload_with_options(
+            r#"{"headers":{"host":"a"},"body":{"n":2}}"#,
+            None,
+            &options,
+        )
+        .expect("processing should work");
+
+        assert_eq!(first.path_hashes["headers"], replay.path_hashes["headers"]);
+        assert_ne!(first.path_hashes["body"], replay.path_hashes["body"]);
+        assert_eq!(
+            first.decoded_value_hash,
+            process_export_payload(r#"{"headers":{"host":"a"},"body":{"n":1}}"#, None)
+                .expect("processing should work")
+                .decoded_value_hash
+        );
+        assert!(
+            process_export_payload(r#"{"q":"x"}"#, None)
+                .expect("processing should work")
+                .path_hashes
+                .is_empty()
+        );
+    }
 }
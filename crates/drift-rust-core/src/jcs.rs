@@ -0,0 +1,182 @@
+//! RFC 8785 JSON Canonicalization Scheme (JCS) serializer.
+//!
+//! Object members are ordered by their UTF-16 code units, numbers are written
+//! using the ECMAScript `Number.prototype.toString` algorithm and strings use
+//! the minimal escaping required by the RFC.
+
+use serde_json::Value as JsonValue;
+use std::io::Write;
+
+use crate::error::{CoreError, CoreResult};
+use crate::hash::{CanonicalForm, write_canonical};
+
+/// Writes `v` as JCS. Containers go through the canonical walk shared with
+/// the sorted form ([`write_canonical`]), which orders members by UTF-16 code
+/// units and comes back here for keys and scalars.
+///
+/// Numbers are written as IEEE doubles, as the RFC requires. Integers beyond
+/// 2^53 round like in any I-JSON document, but a lossless number (a
+/// `lossless_numbers` literal no 64-bit type holds) is rejected by the
+/// canonical walk rather than hashed like its rounded neighbours.
+pub(crate) fn write_jcs<W: Write>(v: &JsonValue, out: &mut W) -> CoreResult<()> {
+    match v {
+        JsonValue::Null => write_raw(out, b"null"),
+        JsonValue::Bool(true) => write_raw(out, b"true"),
+        JsonValue::Bool(false) => write_raw(out, b"false"),
+        JsonValue::Number(n) => {
+            let f = n.as_f64().ok_or_else(|| {
+                CoreError::SerializationError(format!("number {n} is not representable in JCS"))
+            })?;
+            write_raw(out, format_ecmascript_number(f)?.as_bytes())
+        }
+        JsonValue::String(s) => write_jcs_string(s, out),
+        JsonValue::Array(_) | JsonValue::Object(_) => write_canonical(v, CanonicalForm::Jcs, out),
+    }
+}
+
+fn write_raw<W: Write>(out: &mut W, bytes: &[u8]) -> CoreResult<()> {
+    out.write_all(bytes)
+        .map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+pub(crate) fn write_jcs_string<W: Write>(s: &str, out: &mut W) -> CoreResult<()> {
+    // serde_json already emits the RFC 8785 string form: short escapes for
+    // \b \t \n \f \r, lowercase \u00xx for other control characters, and
+    // everything else (including non-ASCII) verbatim.
+    serde_json::to_writer(out, s).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+/// Formats a finite double the way ECMAScript `Number.prototype.toString`
+/// does (RFC 8785 section 3.2.2.3).
+pub(crate) fn format_ecmascript_number(f: f64) -> CoreResult<String> {
+    if !f.is_finite() {
+        return Err(CoreError::SerializationError(format!(
+            "non-finite number {f} cannot be canonicalized"
+        )));
+    }
+    if f == 0.0 {
+        return Ok("0".to_string());
+    }
+    if f < 0.0 {
+        return Ok(format!("-{}", format_ecmascript_number(-f)?));
+    }
+
+    let (digits, n) = shortest_decimal(f);
+    let k = digits.len() as i32;
+    let mut out = String::with_capacity(digits.len() + 8);
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-n) as usize));
+        out.push_str(&digits);
+    } else {
+        let exponent = n - 1;
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if exponent < 0 { '-' } else { '+' });
+        out.push_str(&exponent.unsigned_abs().to_string());
+    }
+    Ok(out)
+}
+
+/// Returns the shortest round-trip significant digits of a positive finite
+/// double and the decimal exponent `n` such that `f = 0.digits * 10^n`.
+fn shortest_decimal(f: f64) -> (String, i32) {
+    let mut buffer = ryu::Buffer::new();
+    let formatted = buffer.format_finite(f);
+    let (mantissa, exponent) = match formatted.split_once('e') {
+        Some((m, e)) => (m, e.parse::<i32>().unwrap_or(0)),
+        None => (formatted, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let mut n = int_part.len() as i32 + exponent;
+    let mut digits: String = int_part.chars().chain(frac_part.chars()).collect();
+    let leading_zeros = digits.len() - digits.trim_start_matches('0').len();
+    n -= leading_zeros as i32;
+    digits.drain(..leading_zeros);
+    let trimmed_len = digits.trim_end_matches('0').len();
+    digits.truncate(trimmed_len);
+    (digits, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalize::NumberParsing;
+    use crate::numbers::parse_literals;
+
+    #[test]
+    fn formats_numbers_like_ecmascript() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (123456789.0, "123456789"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (4.5e-320, "4.5e-320"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (333333333.3333333, "333333333.3333333"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format_ecmascript_number(input).expect("finite numbers should format"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_lossless_literals_a_double_cannot_hold() {
+        let write = |literal: &str| {
+            let value =
+                parse_literals(literal, NumberParsing::Lossless).expect("literal should parse");
+            let mut bytes = Vec::new();
+            write_jcs(&value, &mut bytes).map(|()| String::from_utf8_lossy(&bytes).into_owned())
+        };
+
+        assert_eq!(
+            write("[18446744073709551615,1.50]").expect("64-bit values should write"),
+            "[18446744073709552000,1.5]"
+        );
+        for literal in ["123456789012345678901234567890", "0.10000000000000000001"] {
+            assert!(matches!(
+                write(literal),
+                Err(CoreError::SerializationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn orders_keys_by_utf16_code_units_and_escapes_minimally() {
+        // U+1F600 (surrogate pair 0xD83D..) sorts before U+FFFD in UTF-16 but
+        // after it in UTF-8 byte order.
+        let value = serde_json::json!({
+            "\u{fffd}": 1,
+            "\u{1f600}": 2,
+            "b": "line\nbreak \u{7f} é",
+            "a": [1.0, 2.50, null, true],
+        });
+        let mut bytes = Vec::new();
+        write_jcs(&value, &mut bytes).expect("jcs serialization should succeed");
+
+        assert_eq!(
+            String::from_utf8(bytes).expect("jcs output is utf-8"),
+            "{\"a\":[1,2.5,null,true],\"b\":\"line\\nbreak \u{7f} é\",\"\u{1f600}\":2,\"\u{fffd}\":1}"
+        );
+    }
+}
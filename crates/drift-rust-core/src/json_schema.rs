@@ -0,0 +1,355 @@
+use prost::Message;
+use serde_json::{Map, Value as JsonValue};
+use tusk_drift_schemas::tusk::drift::core::v1::JsonSchema;
+
+use crate::error::{CoreError, CoreResult};
+use crate::normalize::parse_json;
+use crate::schema::merge_schemas;
+use crate::span_proto::json_schema_from_value;
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Drift type codes with an exact JSON Schema type. UNDEFINED (5),
+/// UNORDERED_LIST (8) and FUNCTION (9) export as their nearest type (or none)
+/// plus `x-drift-type`, so they survive a round trip.
+fn standard_type(code: i64) -> Option<&'static str> {
+    Some(match code {
+        1 => "number",
+        2 => "string",
+        3 => "boolean",
+        4 => "null",
+        6 => "object",
+        7 | 8 => "array",
+        _ => return None,
+    })
+}
+
+fn drift_type(name: &str) -> Option<i64> {
+    Some(match name {
+        "number" | "integer" => 1,
+        "string" => 2,
+        "boolean" => 3,
+        "null" => 4,
+        "object" => 6,
+        "array" => 7,
+        _ => return None,
+    })
+}
+
+/// Drift keys that become `x-drift-*` extension keywords, and back.
+const EXTENSIONS: [(&str, &str); 5] = [
+    ("encoding", "x-drift-encoding"),
+    ("decoded_type", "x-drift-decoded-type"),
+    ("match_importance", "x-drift-match-importance"),
+    ("truncated", "x-drift-truncated"),
+    ("lossless_number", "x-drift-lossless-number"),
+];
+
+/// Converts a Drift schema in its JSON form (`decoded_schema_json`) to a
+/// draft 2020-12 JSON Schema. Union `types` and `nullable` become a `type`
+/// array, properties without `optional` are `required`, and Drift-only
+/// information is kept in `x-drift-*` keywords.
+pub fn drift_schema_to_json_schema(schema_json: &str) -> CoreResult<String> {
+    let mut out = to_json_schema(&parse_json(schema_json)?);
+    if let JsonValue::Object(root) = &mut out {
+        root.insert("$schema".to_string(), DIALECT.into());
+    }
+    serde_json::to_string(&out).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+/// Converts a draft 2020-12 JSON Schema to a Drift schema in its JSON form.
+/// `integer` reads as NUMBER, properties missing from `required` are
+/// `optional`, and `anyOf`/`oneOf` alternatives are merged into one union
+/// node. Keywords Drift has no place for (`$ref`, `enum`, formats, bounds,
+/// ...) are dropped.
+pub fn json_schema_to_drift_schema(json_schema: &str) -> CoreResult<String> {
+    let out = from_json_schema(&parse_json(json_schema)?)?;
+    serde_json::to_string(&out).map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+/// [`drift_schema_to_json_schema`] for an encoded `JsonSchema` protobuf,
+/// the form spans carry.
+pub fn drift_schema_proto_to_json_schema(schema_proto_bytes: &[u8]) -> CoreResult<String> {
+    let schema = JsonSchema::decode(schema_proto_bytes)
+        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
+    let schema_json = serde_json::to_string(&value_from_json_schema_proto(&schema))
+        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
+    drift_schema_to_json_schema(&schema_json)
+}
+
+/// [`json_schema_to_drift_schema`] encoded as a `JsonSchema` protobuf. The
+/// proto has no room for unions, nullability or optional properties, so
+/// those are lost.
+pub fn json_schema_to_drift_schema_proto(json_schema: &str) -> CoreResult<Vec<u8>> {
+    let drift = from_json_schema(&parse_json(json_schema)?)?;
+    Ok(json_schema_from_value(&drift).encode_to_vec())
+}
+
+pub(crate) fn value_from_json_schema_proto(schema: &JsonSchema) -> JsonValue {
+    let mut out = Map::new();
+    out.insert("type".to_string(), schema.r#type.into());
+    out.insert(
+        "properties".to_string(),
+        schema
+            .properties
+            .iter()
+            .map(|(k, v)| (k.clone(), value_from_json_schema_proto(v)))
+            .collect::<Map<_, _>>()
+            .into(),
+    );
+    if let Some(items) = &schema.items {
+        out.insert("items".to_string(), value_from_json_schema_proto(items));
+    }
+    if let Some(encoding) = schema.encoding {
+        out.insert("encoding".to_string(), encoding.into());
+    }
+    if let Some(decoded_type) = schema.decoded_type {
+        out.insert("decoded_type".to_string(), decoded_type.into());
+    }
+    if let Some(n) = schema
+        .match_importance
+        .and_then(serde_json::Number::from_f64)
+    {
+        out.insert("match_importance".to_string(), n.into());
+    }
+    JsonValue::Object(out)
+}
+
+fn to_json_schema(drift: &JsonValue) -> JsonValue {
+    let Some(node) = drift.as_object() else {
+        return JsonValue::Object(Map::new());
+    };
+    let mut out = Map::new();
+    let code = node.get("type").and_then(JsonValue::as_i64).unwrap_or(0);
+    let mut types: Vec<&str> = match node.get("types") {
+        Some(JsonValue::Array(codes)) => codes
+            .iter()
+            .filter_map(JsonValue::as_i64)
+            .filter_map(standard_type)
+            .collect(),
+        _ => standard_type(code).into_iter().collect(),
+    };
+    if node.get("nullable") == Some(&JsonValue::Bool(true)) && !types.contains(&"null") {
+        types.push("null");
+    }
+    match types[..] {
+        [] => {}
+        [only] => {
+            out.insert("type".to_string(), only.into());
+        }
+        _ => {
+            out.insert("type".to_string(), types.into());
+        }
+    }
+    if matches!(code, 5 | 8 | 9) {
+        out.insert("x-drift-type".to_string(), code.into());
+    }
+
+    if let Some(JsonValue::Object(properties)) = node.get("properties")
+        && (code == 6 || !properties.is_empty())
+    {
+        out.insert(
+            "properties".to_string(),
+            properties
+                .iter()
+                .map(|(k, v)| (k.clone(), to_json_schema(v)))
+                .collect::<Map<_, _>>()
+                .into(),
+        );
+        let required: Vec<&str> = properties
+            .iter()
+            .filter(|(_, v)| v.get("optional") != Some(&JsonValue::Bool(true)))
+            .map(|(k, _)| k.as_str())
+            .collect();
+        if !required.is_empty() {
+            out.insert("required".to_string(), required.into());
+        }
+    }
+    if let Some(items) = node.get("items") {
+        out.insert("items".to_string(), to_json_schema(items));
+    }
+    for (drift_key, extension) in EXTENSIONS {
+        if let Some(value) = node.get(drift_key) {
+            out.insert(extension.to_string(), value.clone());
+        }
+    }
+    JsonValue::Object(out)
+}
+
+fn from_json_schema(schema: &JsonValue) -> CoreResult<JsonValue> {
+    let node = match schema {
+        JsonValue::Object(node) => node,
+        JsonValue::Bool(_) => return Ok(serde_json::json!({"type": 0, "properties": {}})),
+        _ => {
+            return Err(CoreError::InvalidJson(
+                "a JSON Schema must be an object or a boolean".to_string(),
+            ));
+        }
+    };
+    let alternatives = ["anyOf", "oneOf"]
+        .iter()
+        .find_map(|keyword| node.get(*keyword).and_then(JsonValue::as_array));
+    if let Some(alternatives) = alternatives {
+        let mut merged = None;
+        for alternative in alternatives {
+            let alternative = from_json_schema(alternative)?;
+            merged = Some(match merged {
+                Some(acc) => merge_schemas(acc, alternative),
+                None => alternative,
+            });
+        }
+        if let Some(merged) = merged {
+            return Ok(merged);
+        }
+    }
+
+    let mut codes: Vec<i64> = match node.get("type") {
+        Some(JsonValue::String(name)) => drift_type(name).into_iter().collect(),
+        Some(JsonValue::Array(names)) => names
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .filter_map(drift_type)
+            .collect(),
+        _ if node.contains_key("properties") => vec![6],
+        _ if node.contains_key("items") => vec![7],
+        _ => Vec::new(),
+    };
+    codes.sort_unstable();
+    codes.dedup();
+    let nullable = codes.contains(&4) && codes.len() > 1;
+    codes.retain(|&code| !nullable || code != 4);
+
+    let mut out = Map::new();
+    let code = match (
+        node.get("x-drift-type").and_then(JsonValue::as_i64),
+        &codes[..],
+    ) {
+        (Some(code), _) => code,
+        (None, [only]) => *only,
+        _ => 0,
+    };
+    out.insert("type".to_string(), code.into());
+    if codes.len() > 1 {
+        out.insert("types".to_string(), codes.into());
+    }
+    if nullable {
+        out.insert("nullable".to_string(), JsonValue::Bool(true));
+    }
+
+    let required: Vec<&str> = node
+        .get("required")
+        .and_then(JsonValue::as_array)
+        .map(|names| names.iter().filter_map(JsonValue::as_str).collect())
+        .unwrap_or_default();
+    let mut properties = Map::new();
+    if let Some(JsonValue::Object(standard)) = node.get("properties") {
+        for (name, property) in standard {
+            let mut property = from_json_schema(property)?;
+            if !required.contains(&name.as_str())
+                && let JsonValue::Object(property) = &mut property
+            {
+                property.insert("optional".to_string(), JsonValue::Bool(true));
+            }
+            properties.insert(name.clone(), property);
+        }
+    }
+    out.insert("properties".to_string(), JsonValue::Object(properties));
+    if let Some(items @ JsonValue::Object(_)) = node.get("items") {
+        out.insert("items".to_string(), from_json_schema(items)?);
+    }
+    for (drift_key, extension) in EXTENSIONS {
+        if let Some(value) = node.get(extension) {
+            out.insert(drift_key.to_string(), value.clone());
+        }
+    }
+    Ok(JsonValue::Object(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn drift_schemas_round_trip_through_json_schema() {
+        let drift = json!({
+            "type": 6,
+            "properties": {
+                "body": {"type": 6, "properties": {}, "encoding": 1, "decoded_type": 1},
+                "id": {"type": 0, "types": [1, 2], "nullable": true, "properties": {}},
+                "tags": {
+                    "type": 8,
+                    "properties": {},
+                    "items": {"type": 2, "properties": {}},
+                    "optional": true
+                }
+            }
+        });
+        let exported: JsonValue = serde_json::from_str(
+            &drift_schema_to_json_schema(&drift.to_string()).expect("export should succeed"),
+        )
+        .expect("export should be JSON");
+
+        assert_eq!(
+            exported,
+            json!({
+                "$schema": DIALECT,
+                "type": "object",
+                "properties": {
+                    "body": {
+                        "type": "object",
+                        "properties": {},
+                        "x-drift-encoding": 1,
+                        "x-drift-decoded-type": 1
+                    },
+                    "id": {"type": ["number", "string", "null"]},
+                    "tags": {"type": "array", "x-drift-type": 8, "items": {"type": "string"}}
+                },
+                "required": ["body", "id"]
+            })
+        );
+        let imported: JsonValue = serde_json::from_str(
+            &json_schema_to_drift_schema(&exported.to_string()).expect("import should succeed"),
+        )
+        .expect("import should be JSON");
+        assert_eq!(imported, drift);
+    }
+
+    #[test]
+    fn imports_standard_keywords_and_proto_form() {
+        let standard = json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer", "minimum": 0},
+                "value": {"anyOf": [{"type": "string"}, {"type": "null"}]}
+            },
+            "required": ["count"]
+        });
+        let imported: JsonValue = serde_json::from_str(
+            &json_schema_to_drift_schema(&standard.to_string()).expect("import should succeed"),
+        )
+        .expect("import should be JSON");
+        assert_eq!(
+            imported,
+            json!({
+                "type": 6,
+                "properties": {
+                    "count": {"type": 1, "properties": {}},
+                    "value": {"type": 2, "nullable": true, "properties": {}, "optional": true}
+                }
+            })
+        );
+
+        let proto = json_schema_to_drift_schema_proto(&standard.to_string())
+            .expect("proto import should succeed");
+        let exported: JsonValue = serde_json::from_str(
+            &drift_schema_proto_to_json_schema(&proto).expect("proto export should succeed"),
+        )
+        .expect("export should be JSON");
+        assert_eq!(exported["properties"]["count"], json!({"type": "number"}));
+        assert!(matches!(
+            json_schema_to_drift_schema("3"),
+            Err(CoreError::InvalidJson(_))
+        ));
+    }
+}
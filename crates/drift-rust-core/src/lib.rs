@@ -1,21 +1,65 @@
+mod accumulate;
+mod batch;
+mod decoded;
+mod detect;
+mod encoding;
 mod error;
 mod export_request_proto;
+mod fingerprint;
 mod hash;
+mod jcs;
+mod json_schema;
+mod limits;
+mod merge;
 mod normalize;
+mod numbers;
+mod path;
 mod payload;
 mod protobuf_struct;
+mod redact;
 mod schema;
+mod schema_diff;
+mod shape;
 mod span_proto;
 mod types;
+mod validate;
+mod volatile;
 
+pub use accumulate::{
+    SchemaAccumulator, accumulate_payload_schemas, accumulate_schemas, merge_schema_accumulators,
+};
+pub use batch::{normalize_and_hash_batch, process_export_payload_batch};
+pub use detect::{DetectedRedaction, Detector, DetectorOptions};
 pub use error::{CoreError, CoreResult};
 pub use export_request_proto::build_export_spans_request_bytes;
-pub use hash::{deterministic_hash, normalize_and_hash};
-pub use normalize::normalize_json;
-pub use payload::{process_export_payload, process_export_payload_value};
+pub use fingerprint::fingerprint_similarity;
+pub use hash::{
+    CanonicalForm, HashAlgorithm, HashOptions, HashScheme, deterministic_hash,
+    deterministic_hash_with_options, normalize_and_hash, normalize_and_hash_with_options,
+};
+pub use json_schema::{
+    drift_schema_proto_to_json_schema, drift_schema_to_json_schema, json_schema_to_drift_schema,
+    json_schema_to_drift_schema_proto,
+};
+pub use limits::PayloadLimits;
+pub use normalize::{
+    NormalizeOptions, normalize_json, normalize_json_value, normalize_json_with_options,
+};
+pub use numbers::{lossless_literal, number_from_literal};
+pub use path::JsonPath;
+pub use payload::{
+    process_export_payload, process_export_payload_value,
+    process_export_payload_value_with_options, process_export_payload_with_options,
+};
 pub use protobuf_struct::{
     object_to_protobuf_struct, object_to_protobuf_struct_bytes,
     object_to_protobuf_struct_field_count,
 };
+pub use redact::{RedactionAction, RedactionRule};
+pub use schema_diff::{SchemaChange, SchemaChangeKind, diff_schemas};
 pub use span_proto::build_span_proto_bytes;
-pub use types::{BuildSpanProtoInput, ExportPayloadResult, ExportPayloadValueResult};
+pub use types::{
+    BuildSpanProtoInput, ExportPayloadOptions, ExportPayloadResult, ExportPayloadValueResult,
+};
+pub use validate::{SchemaViolation, validate_against_schema, validate_against_schema_proto};
+pub use volatile::{VolatileOptions, VolatilePattern};
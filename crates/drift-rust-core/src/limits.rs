@@ -0,0 +1,583 @@
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io::Write;
+use std::ops::Range;
+
+use crate::error::{CoreError, CoreResult};
+use crate::normalize::NormalizeOptions;
+use crate::numbers::{WithLiterals, lossless_literal};
+use crate::path::PathSegment;
+
+/// Guards against oversized payloads. By default anything over a limit is
+/// truncated and replaced by a deterministic `<TRUNCATED:...>` marker; with
+/// `strict` set the payload is rejected with [`CoreError::LimitExceeded`].
+///
+/// Every limit is off by default. Nesting is bounded regardless: JSON text
+/// nested deeper than 127 levels fails to parse, and values passed in
+/// directly or decoded from merge members that nest deeper are rejected with
+/// [`CoreError::LimitExceeded`], so the recursive walks (normalization,
+/// hashing, Struct encoding) cannot exhaust the stack.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PayloadLimits {
+    /// Serialized size of the whole payload. Truncation replaces the payload
+    /// with `"<TRUNCATED:BYTES:n>"`.
+    pub max_bytes: Option<usize>,
+    /// Number of nested arrays/objects. Containers below the limit become
+    /// `"<TRUNCATED:DEPTH>"`.
+    pub max_depth: Option<usize>,
+    /// Arrays keep their first elements followed by `"<TRUNCATED:ARRAY:n>"`.
+    pub max_array_len: Option<usize>,
+    /// Strings keep their first characters followed by
+    /// `<TRUNCATED:STRING:n>`, with `n` the original length in characters.
+    pub max_string_len: Option<usize>,
+    pub strict: bool,
+}
+
+/// Deepest array/object nesting the core accepts, whatever the limits say.
+/// It matches serde_json's parser recursion limit, so a value accepted here
+/// could also have come from JSON text.
+pub(crate) const MAX_NESTING: usize = 127;
+
+const MARKER_PREFIX: &str = "<TRUNCATED:";
+const DEPTH_MARKER: &str = "<TRUNCATED:DEPTH>";
+
+impl PayloadLimits {
+    pub(crate) fn is_noop(&self) -> bool {
+        self.max_bytes.is_none()
+            && self.max_depth.is_none()
+            && self.max_array_len.is_none()
+            && self.max_string_len.is_none()
+    }
+
+    fn exceeded(&self, what: String) -> CoreResult<()> {
+        if self.strict {
+            Err(CoreError::LimitExceeded(what))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Locations of the values a limit shortened, recorded as the limits apply
+/// so schemas can flag them with `"truncated": true` without guessing from
+/// the markers, which payloads may contain themselves.
+#[derive(Debug, Default)]
+pub(crate) struct Truncations {
+    paths: HashSet<Vec<PathSegment>>,
+}
+
+impl Truncations {
+    pub(crate) fn contains(&self, path: &[PathSegment]) -> bool {
+        self.paths.contains(path)
+    }
+
+    fn record(&mut self, path: &[PathSegment]) {
+        self.paths.insert(path.to_vec());
+    }
+
+    /// The same locations after `options` normalized the value: keys below a
+    /// `lowercase_keys` member are lowercased there too.
+    pub(crate) fn normalized(self, options: &NormalizeOptions) -> Self {
+        if options.lowercase_keys.is_empty() {
+            return self;
+        }
+        let paths = self
+            .paths
+            .into_iter()
+            .map(|path| {
+                let mut out: Vec<PathSegment> = Vec::with_capacity(path.len());
+                for segment in path {
+                    let lowercase = matches!(
+                        out.last(),
+                        Some(PathSegment::Key(parent)) if options.lowercase_keys.contains(parent)
+                    );
+                    out.push(match segment {
+                        PathSegment::Key(k) if lowercase => PathSegment::Key(k.to_lowercase()),
+                        other => other,
+                    });
+                }
+                out
+            })
+            .collect();
+        Self { paths }
+    }
+}
+
+/// Applies the byte and depth limits to raw payload JSON before it is parsed,
+/// so oversized or deeply nested text never reaches the parser.
+pub(crate) fn limit_payload_text<'a>(
+    payload_json: &'a str,
+    limits: &PayloadLimits,
+    truncated: &mut Truncations,
+) -> CoreResult<Cow<'a, str>> {
+    if let Some(max) = limits.max_bytes
+        && payload_json.len() > max
+    {
+        limits.exceeded(format!(
+            "payload is {} bytes, max {max}",
+            payload_json.len()
+        ))?;
+        truncated.record(&[]);
+        return Ok(Cow::Owned(bytes_marker(payload_json.len()).to_string()));
+    }
+    if let Some(max) = limits.max_depth
+        && text_depth(payload_json) > max
+    {
+        limits.exceeded(format!("payload nests deeper than {max} levels"))?;
+        return Ok(Cow::Owned(truncate_text_depth(
+            payload_json,
+            max,
+            truncated,
+        )));
+    }
+    Ok(Cow::Borrowed(payload_json))
+}
+
+/// Applies every limit to an already parsed value.
+pub(crate) fn limit_value<'a>(
+    value: &'a JsonValue,
+    limits: &PayloadLimits,
+    truncated: &mut Truncations,
+) -> CoreResult<Cow<'a, JsonValue>> {
+    if limits.is_noop() {
+        return Ok(Cow::Borrowed(value));
+    }
+    let limited = limit_node(value, &mut Vec::new(), limits, truncated)?;
+    if let Some(max) = limits.max_bytes {
+        let mut counter = ByteCounter { count: 0, max };
+        if serde_json::to_writer(&mut counter, &WithLiterals(&limited)).is_err() {
+            limits.exceeded(format!("payload is over {max} bytes"))?;
+            let len = serde_json::to_vec(&WithLiterals(&limited))
+                .map_err(|e| CoreError::SerializationError(e.to_string()))?
+                .len();
+            truncated.record(&[]);
+            return Ok(Cow::Owned(bytes_marker(len)));
+        }
+    }
+    Ok(Cow::Owned(limited))
+}
+
+/// Applies the depth, array and string limits to `value`, which sits at
+/// `path`, recording what gets shortened in `truncated`.
+pub(crate) fn limit_node(
+    value: &JsonValue,
+    path: &mut Vec<PathSegment>,
+    limits: &PayloadLimits,
+    truncated: &mut Truncations,
+) -> CoreResult<JsonValue> {
+    let depth = path.len();
+    match value {
+        _ if lossless_literal(value).is_some() => Ok(value.clone()),
+        JsonValue::Array(_) | JsonValue::Object(_)
+            if limits.max_depth.is_some_and(|max| depth >= max) =>
+        {
+            limits.exceeded(format!("payload nests deeper than {depth} levels"))?;
+            truncated.record(path);
+            Ok(JsonValue::String(DEPTH_MARKER.to_string()))
+        }
+        JsonValue::String(s) => match limits.max_string_len {
+            Some(max) if s.chars().count() > max => {
+                let len = s.chars().count();
+                limits.exceeded(format!("string of {len} characters, max {max}"))?;
+                truncated.record(path);
+                let kept: String = s.chars().take(max).collect();
+                Ok(JsonValue::String(format!(
+                    "{kept}{MARKER_PREFIX}STRING:{len}>"
+                )))
+            }
+            _ => Ok(value.clone()),
+        },
+        JsonValue::Array(arr) => {
+            let keep = match limits.max_array_len {
+                Some(max) if arr.len() > max => {
+                    limits.exceeded(format!("array of {} elements, max {max}", arr.len()))?;
+                    max
+                }
+                _ => arr.len(),
+            };
+            let mut out = Vec::with_capacity(keep + 1);
+            for (i, child) in arr[..keep].iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                out.push(limit_node(child, path, limits, truncated)?);
+                path.pop();
+            }
+            if keep < arr.len() {
+                truncated.record(path);
+                out.push(JsonValue::String(format!(
+                    "{MARKER_PREFIX}ARRAY:{}>",
+                    arr.len()
+                )));
+            }
+            Ok(JsonValue::Array(out))
+        }
+        JsonValue::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (k, child) in map {
+                path.push(PathSegment::Key(k.clone()));
+                out.insert(k.clone(), limit_node(child, path, limits, truncated)?);
+                path.pop();
+            }
+            Ok(JsonValue::Object(out))
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Rejects `value`, which sits below `depth` enclosing containers, when it
+/// nests deeper than [`MAX_NESTING`]. Walks with an explicit stack, since the
+/// value may be too deep to recurse into.
+pub(crate) fn check_nesting(value: &JsonValue, depth: usize) -> CoreResult<()> {
+    let mut pending = vec![(value, depth)];
+    while let Some((value, depth)) = pending.pop() {
+        let children: Box<dyn Iterator<Item = &JsonValue>> = match value {
+            _ if lossless_literal(value).is_some() => continue,
+            JsonValue::Array(arr) => Box::new(arr.iter()),
+            JsonValue::Object(map) => Box::new(map.values()),
+            _ => continue,
+        };
+        if depth >= MAX_NESTING {
+            return Err(CoreError::LimitExceeded(format!(
+                "payload nests deeper than {MAX_NESTING} levels"
+            )));
+        }
+        pending.extend(children.map(|child| (child, depth + 1)));
+    }
+    Ok(())
+}
+
+fn bytes_marker(len: usize) -> JsonValue {
+    JsonValue::String(format!("{MARKER_PREFIX}BYTES:{len}>"))
+}
+
+struct ByteCounter {
+    count: usize,
+    max: usize,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count += buf.len();
+        if self.count > self.max {
+            return Err(std::io::Error::other("byte limit exceeded"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Maximum array/object nesting of JSON text, ignoring brackets in strings.
+fn text_depth(text: &str) -> usize {
+    let (mut depth, mut max) = (0usize, 0usize);
+    scan_structure(text, |token, _| match token {
+        b'[' | b'{' => {
+            depth += 1;
+            max = max.max(depth);
+        }
+        b']' | b'}' => depth = depth.saturating_sub(1),
+        _ => {}
+    });
+    max
+}
+
+/// An open container of JSON text being scanned, and where its current
+/// member sits.
+struct TextContainer {
+    array: bool,
+    index: usize,
+    key: String,
+    after_colon: bool,
+}
+
+impl TextContainer {
+    fn member(&self) -> PathSegment {
+        if self.array {
+            PathSegment::Index(self.index)
+        } else {
+            PathSegment::Key(self.key.clone())
+        }
+    }
+}
+
+/// Rewrites JSON text so containers nested deeper than `max_depth` become the
+/// depth marker, recording where each one was. Invalid JSON stays invalid
+/// and is reported by the parser.
+fn truncate_text_depth(text: &str, max_depth: usize, truncated: &mut Truncations) -> String {
+    let mut out = String::with_capacity(text.len());
+    let (mut depth, mut copied_to, mut skip_from) = (0usize, 0usize, None);
+    let mut open: Vec<TextContainer> = Vec::new();
+    scan_structure(text, |token, span| {
+        let i = span.start;
+        match token {
+            b'[' | b'{' => {
+                depth += 1;
+                if depth == max_depth + 1 {
+                    let path: Vec<PathSegment> = open.iter().map(TextContainer::member).collect();
+                    truncated.record(&path);
+                    out.push_str(&text[copied_to..i]);
+                    skip_from = Some(i);
+                } else if depth <= max_depth {
+                    open.push(TextContainer {
+                        array: token == b'[',
+                        index: 0,
+                        key: String::new(),
+                        after_colon: false,
+                    });
+                }
+            }
+            b']' | b'}' => {
+                if depth == max_depth + 1 && skip_from.take().is_some() {
+                    out.push('"');
+                    out.push_str(DEPTH_MARKER);
+                    out.push('"');
+                    copied_to = i + 1;
+                } else if depth <= max_depth {
+                    open.pop();
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ if depth > max_depth => {}
+            b',' => {
+                if let Some(container) = open.last_mut() {
+                    container.index += 1;
+                    container.after_colon = false;
+                }
+            }
+            b':' => {
+                if let Some(container) = open.last_mut() {
+                    container.after_colon = true;
+                }
+            }
+            _ => {
+                if let Some(container) = open.last_mut()
+                    && !container.array
+                    && !container.after_colon
+                {
+                    container.key = serde_json::from_str(&text[span]).unwrap_or_default();
+                }
+            }
+        }
+    });
+    if skip_from.is_none() {
+        out.push_str(&text[copied_to..]);
+    }
+    out
+}
+
+/// Calls `f` with every bracket, comma and colon outside of string literals
+/// and its offset, and with `b'"'` and the span of every string literal.
+fn scan_structure(text: &str, mut f: impl FnMut(u8, Range<usize>)) {
+    let (mut string_from, mut escaped) = (None, false);
+    for (i, byte) in text.bytes().enumerate() {
+        if let Some(from) = string_from {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    string_from = None;
+                    f(b'"', from..i + 1);
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => string_from = Some(i),
+            b'[' | b'{' | b']' | b'}' | b',' | b':' => f(byte, i..i + 1),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{
+        process_export_payload, process_export_payload_value, process_export_payload_with_options,
+    };
+    use crate::types::ExportPayloadOptions;
+
+    fn limits(limits_json: &str) -> PayloadLimits {
+        serde_json::from_str(limits_json).expect("limits should parse")
+    }
+
+    #[test]
+    fn truncates_depth_arrays_and_strings_with_markers() {
+        let value = serde_json::json!({
+            "s": "abcdef",
+            "a": [1, 2, 3, 4],
+            "deep": {"x": {"y": 1}},
+        });
+        let mut truncated = Truncations::default();
+        let limited = limit_value(
+            &value,
+            &limits(r#"{"max_depth":2,"max_array_len":2,"max_string_len":3}"#),
+            &mut truncated,
+        )
+        .expect("truncation should succeed");
+
+        assert_eq!(
+            limited.as_ref(),
+            &serde_json::json!({
+                "s": "abc<TRUNCATED:STRING:6>",
+                "a": [1, 2, "<TRUNCATED:ARRAY:4>"],
+                "deep": {"x": "<TRUNCATED:DEPTH>"},
+            })
+        );
+        let key = |k: &str| PathSegment::Key(k.to_string());
+        assert!(truncated.contains(&[key("s")]));
+        assert!(truncated.contains(&[key("a")]));
+        assert!(truncated.contains(&[key("deep"), key("x")]));
+        assert!(!truncated.contains(&[key("deep")]));
+    }
+
+    #[test]
+    fn deep_text_is_truncated_before_parsing() {
+        let deep = format!(
+            "{{\"a\":\"]\",\"b\":{}1{}}}",
+            "[".repeat(10_000),
+            "]".repeat(10_000)
+        );
+        let mut truncated = Truncations::default();
+        let limited = limit_payload_text(&deep, &limits(r#"{"max_depth":3}"#), &mut truncated)
+            .expect("should truncate");
+
+        assert_eq!(limited, r#"{"a":"]","b":[["<TRUNCATED:DEPTH>"]]}"#);
+        assert!(truncated.contains(&[
+            PathSegment::Key("b".to_string()),
+            PathSegment::Index(0),
+            PathSegment::Index(0)
+        ]));
+    }
+
+    #[test]
+    fn nesting_is_bounded_like_the_parser() {
+        let nested = |levels: usize| {
+            let text = format!("{}{}", "[".repeat(levels), "]".repeat(levels));
+            (
+                serde_json::from_str::<JsonValue>(&text).is_ok(),
+                (0..levels).fold(JsonValue::Null, |inner, _| JsonValue::Array(vec![inner])),
+            )
+        };
+        let (parses, value) = nested(MAX_NESTING);
+        assert!(parses);
+        assert!(check_nesting(&value, 0).is_ok());
+        assert!(matches!(
+            check_nesting(&value, 1),
+            Err(CoreError::LimitExceeded(_))
+        ));
+        let (parses, value) = nested(MAX_NESTING + 1);
+        assert!(!parses);
+        assert!(matches!(
+            check_nesting(&value, 0),
+            Err(CoreError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn strict_limits_fail_instead_of_truncating() {
+        let strict = limits(r#"{"max_bytes":8,"strict":true}"#);
+        let err = limit_payload_text(
+            r#"{"k":"long value"}"#,
+            &strict,
+            &mut Truncations::default(),
+        )
+        .expect_err("oversized payload should fail");
+        assert!(matches!(err, CoreError::LimitExceeded(_)));
+
+        let err = limit_value(
+            &serde_json::json!({"k": "long value"}),
+            &strict,
+            &mut Truncations::default(),
+        )
+        .expect_err("oversized value should fail");
+        assert!(matches!(err, CoreError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn truncates_past_limits_and_marks_the_schema() {
+        let options =
+            ExportPayloadOptions::from_json(r#"{"limits":{"max_depth":2,"max_array_len":1}}"#)
+                .expect("options should parse");
+        // Deeper than serde_json's own recursion limit of 128.
+        let payload = format!(
+            r#"{{"deep":{}{}, "list":[1,2]}}"#,
+            "[".repeat(500),
+            "]".repeat(500)
+        );
+        let result = process_export_payload_with_options(&payload, None, &options)
+            .expect("processing should work");
+
+        assert_eq!(
+            result.normalized_json,
+            r#"{"deep":["<TRUNCATED:DEPTH>"],"list":[1,"<TRUNCATED:ARRAY:2>"]}"#
+        );
+        let schema: JsonValue =
+            serde_json::from_str(&result.decoded_schema_json).expect("schema should parse");
+        assert_eq!(
+            schema["properties"]["list"]["truncated"],
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            schema["properties"]["deep"]["items"]["truncated"],
+            serde_json::json!(true)
+        );
+    }
+
+    #[test]
+    fn only_values_the_limits_shortened_are_flagged() {
+        let options = ExportPayloadOptions::from_json(
+            r#"{"limits":{"max_depth":3,"max_array_len":1},"normalize":{"lowercase_keys":["h"]}}"#,
+        )
+        .expect("options should parse");
+        let payload =
+            r#"{"h":{"X-Long":[1,2]},"fake":"<TRUNCATED:DEPTH>","k":{"key, \":\"":[[1]],"n":[2]}}"#;
+        let result = process_export_payload_with_options(payload, None, &options)
+            .expect("processing should work");
+        let schema: JsonValue =
+            serde_json::from_str(&result.decoded_schema_json).expect("schema should parse");
+
+        assert_eq!(
+            schema["properties"]["h"]["properties"]["x-long"]["truncated"],
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            schema["properties"]["k"]["properties"]["key, \":\""]["items"]["truncated"],
+            serde_json::json!(true)
+        );
+        assert_eq!(schema["properties"]["fake"].get("truncated"), None);
+        assert_eq!(
+            schema["properties"]["k"]["properties"]["n"].get("truncated"),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_values_nested_past_the_parser_limit() {
+        let deep = (0..200).fold(serde_json::json!(1), |inner, _| serde_json::json!([inner]));
+        assert!(matches!(
+            process_export_payload_value(&deep, None),
+            Err(CoreError::LimitExceeded(_))
+        ));
+
+        // Decodes within the bound on its own, but not this far down.
+        let xml = format!("{}1{}", "<a>".repeat(126), "</a>".repeat(126));
+        let payload = serde_json::json!({"x": {"y": {"body": xml}}}).to_string();
+        assert!(matches!(
+            process_export_payload(&payload, Some(r#"{"x.y.body":{"decoded_type":5}}"#)),
+            Err(CoreError::LimitExceeded(_))
+        ));
+
+        // Too deep to decode at all: the member stays raw.
+        let xml = format!("{}1{}", "<a>".repeat(100_000), "</a>".repeat(100_000));
+        let payload = serde_json::json!({ "body": xml }).to_string();
+        let result = process_export_payload(&payload, Some(r#"{"body":{"decoded_type":5}}"#))
+            .expect("processing should work");
+        assert_eq!(result.decoded_json, payload);
+    }
+}
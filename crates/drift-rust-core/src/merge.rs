@@ -0,0 +1,279 @@
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+use crate::decoded::DecodedType;
+use crate::encoding::Encoding;
+use crate::error::{CoreError, CoreResult};
+use crate::limits::{PayloadLimits, Truncations, check_nesting, limit_node};
+use crate::normalize::NumberParsing;
+use crate::path::{
+    JsonPath, PathSegment, for_each_match_mut, for_each_match_with_path_mut, value_at,
+};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MergeRule {
+    #[serde(default)]
+    pub(crate) encoding: Option<i32>,
+    #[serde(default)]
+    pub(crate) decoded_type: Option<i32>,
+    #[serde(default)]
+    pub(crate) match_importance: Option<f64>,
+}
+
+/// A merge rule and the locations it applies to.
+#[derive(Debug)]
+pub(crate) struct SchemaMerge {
+    pub(crate) segments: Vec<PathSegment>,
+    pub(crate) rule: MergeRule,
+}
+
+/// Parses the schema merges map. A key names a literal top-level member of
+/// `root` when one exists (so `"x.y"` keeps meaning the `x.y` field) and is
+/// otherwise read as a [`JsonPath`], e.g. `response.body`, `items[*].payload`
+/// or `/headers/x-payload`. Merges come back shallowest first so a decoded
+/// member can hold further encoded members.
+pub(crate) fn parse_schema_merges(
+    merges_json: Option<&str>,
+    root: &JsonValue,
+) -> CoreResult<Vec<SchemaMerge>> {
+    let Some(merges_json) = merges_json else {
+        return Ok(Vec::new());
+    };
+    let merge_map = serde_json::from_str::<BTreeMap<String, MergeRule>>(merges_json)
+        .map_err(|e| CoreError::InvalidJson(e.to_string()))?;
+    let mut merges: Vec<SchemaMerge> = merge_map
+        .into_iter()
+        .map(|(key, rule)| {
+            let literal = root.get(&key).is_some();
+            let segments = match JsonPath::parse(&key) {
+                Ok(path) if !literal => path.segments().to_vec(),
+                _ => vec![PathSegment::Key(key)],
+            };
+            SchemaMerge { segments, rule }
+        })
+        .collect();
+    merges.sort_by_key(|merge| merge.segments.len());
+    Ok(merges)
+}
+
+/// A member decoded by a merge rule, with what it takes to put it back.
+pub(crate) struct DecodedMember {
+    path: Vec<PathSegment>,
+    raw: JsonValue,
+    encoding: Option<Encoding>,
+    decoded_type: Option<DecodedType>,
+}
+
+/// Decodes every member matched by `merges` in place: its `encoding` first
+/// (see [`Encoding`] for the codes), then its `decoded_type` (see
+/// [`DecodedType`]).
+/// Decoded members are new values the payload limits have not seen yet, so
+/// they are limited (and their nesting bounded) here; `max_bytes` also caps decompressed sizes (a
+/// built-in 64 MiB cap applies without it). Numbers in JSON members are read
+/// like the payload's own.
+pub(crate) fn decode_members(
+    value: &mut JsonValue,
+    merges: &[SchemaMerge],
+    limits: &PayloadLimits,
+    numbers: NumberParsing,
+    truncated: &mut Truncations,
+) -> CoreResult<Vec<DecodedMember>> {
+    let mut members = Vec::new();
+    for merge in merges {
+        let mut result = Ok(());
+        for_each_match_with_path_mut(value, &merge.segments, &mut Vec::new(), &mut |path, v| {
+            let (decoded, encoding, decoded_type) =
+                match decode_member_at(v, path.len(), &merge.rule, limits, numbers) {
+                    Ok(Some(member)) => member,
+                    Ok(None) => return,
+                    Err(e) => {
+                        result = Err(e);
+                        return;
+                    }
+                };
+            let decoded = if limits.is_noop() {
+                decoded
+            } else {
+                match limit_node(&decoded, &mut path.to_vec(), limits, truncated) {
+                    Ok(limited) => limited,
+                    Err(e) => {
+                        result = Err(e);
+                        return;
+                    }
+                }
+            };
+            members.push(DecodedMember {
+                path: path.to_vec(),
+                raw: std::mem::replace(v, decoded),
+                encoding,
+                decoded_type,
+            });
+        });
+        result?;
+    }
+    Ok(members)
+}
+
+/// A decoded member value with the encoding and decoded type that applied.
+pub(crate) type DecodedValue = (JsonValue, Option<Encoding>, Option<DecodedType>);
+
+/// [`decode_member`] for a member below `depth` enclosing containers, failing
+/// when the decoded value would nest past the core's bound.
+pub(crate) fn decode_member_at(
+    value: &JsonValue,
+    depth: usize,
+    rule: &MergeRule,
+    limits: &PayloadLimits,
+    numbers: NumberParsing,
+) -> CoreResult<Option<DecodedValue>> {
+    let Some(member) = decode_member(value, rule, limits, numbers) else {
+        return Ok(None);
+    };
+    check_nesting(&member.0, depth)?;
+    Ok(Some(member))
+}
+
+fn decode_member(
+    value: &JsonValue,
+    rule: &MergeRule,
+    limits: &PayloadLimits,
+    numbers: NumberParsing,
+) -> Option<DecodedValue> {
+    let mut working = value.clone();
+    let mut encoding = None;
+    let mut decoded_type = None;
+    if let Some(candidate) = rule.encoding.and_then(Encoding::from_code)
+        && let JsonValue::String(s) = &working
+        && let Some(text) = candidate.decode(s, limits.max_bytes)
+    {
+        working = JsonValue::String(text);
+        encoding = Some(candidate);
+    }
+    if let Some(code) = rule.decoded_type
+        && let JsonValue::String(s) = &working
+        && let Some((parsed, parsed_type)) = DecodedType::decode(code, s, numbers)
+    {
+        working = parsed;
+        decoded_type = Some(parsed_type);
+    }
+    (encoding.is_some() || decoded_type.is_some()).then_some((working, encoding, decoded_type))
+}
+
+/// Rebuilds the raw form of `after`, a decoded value that was edited (e.g.
+/// redacted) since it looked like `before`. Untouched members get their
+/// original raw value back; edited ones are re-encoded, innermost first.
+pub(crate) fn encode_members(
+    before: &JsonValue,
+    after: &JsonValue,
+    members: &[DecodedMember],
+) -> CoreResult<JsonValue> {
+    let mut raw = after.clone();
+    for member in members.iter().rev() {
+        let (Some(was), Some(now)) = (
+            value_at(before, &member.path),
+            value_at(after, &member.path),
+        ) else {
+            continue;
+        };
+        let replacement = if was == now {
+            member.raw.clone()
+        } else {
+            match value_at(&raw, &member.path) {
+                Some(current) => encode_member(current, member)?,
+                None => continue,
+            }
+        };
+        for_each_match_mut(&mut raw, &member.path, &mut |v| *v = replacement.clone());
+    }
+    Ok(raw)
+}
+
+/// Inverse of [`decode_member`].
+fn encode_member(member: &JsonValue, decoded: &DecodedMember) -> CoreResult<JsonValue> {
+    let mut encoded = member.clone();
+    if let Some(decoded_type) = &decoded.decoded_type {
+        encoded = JsonValue::String(decoded_type.encode(&encoded)?);
+    }
+    if let Some(encoding) = decoded.encoding
+        && let JsonValue::String(s) = &encoded
+    {
+        encoded = JsonValue::String(encoding.encode(s)?);
+    }
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_top_level_keys_win_over_paths() {
+        let root = serde_json::json!({"a.b": "x", "a": {"b": "y"}});
+        let merges = parse_schema_merges(
+            Some(r#"{"a.b":{"decoded_type":1},"/a/b":{"decoded_type":1},"c[*]":{}}"#),
+            &root,
+        )
+        .expect("merges should parse");
+        let segments: Vec<&[PathSegment]> = merges.iter().map(|m| &m.segments[..]).collect();
+
+        assert_eq!(
+            segments,
+            [
+                &[PathSegment::Key("a.b".to_string())][..],
+                &[
+                    PathSegment::Key("a".to_string()),
+                    PathSegment::Key("b".to_string())
+                ],
+                &[PathSegment::Key("c".to_string()), PathSegment::Wildcard],
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_members_decode_and_re_encode_innermost_first() {
+        use base64::Engine as _;
+
+        let inner = base64::engine::general_purpose::STANDARD.encode(r#"{"token":"t","n":1}"#);
+        let mut value = serde_json::json!({
+            "events": [{"data": serde_json::json!({"blob": inner}).to_string()}]
+        });
+        let original = value.clone();
+        let merges = parse_schema_merges(
+            Some(r#"{"events[*].data":{"decoded_type":1},"events[*].data.blob":{"encoding":1,"decoded_type":1}}"#),
+            &value,
+        )
+        .expect("merges should parse");
+        let members = decode_members(
+            &mut value,
+            &merges,
+            &PayloadLimits::default(),
+            NumberParsing::Baseline,
+            &mut Truncations::default(),
+        )
+        .expect("decoding should succeed");
+
+        assert_eq!(
+            value,
+            serde_json::json!({"events": [{"data": {"blob": {"token": "t", "n": 1}}}]})
+        );
+        assert_eq!(
+            encode_members(&value, &value, &members).expect("encoding should succeed"),
+            original
+        );
+
+        let mut edited = value.clone();
+        edited["events"][0]["data"]["blob"]["token"] = serde_json::json!("<REDACTED>");
+        let raw = encode_members(&value, &edited, &members).expect("encoding should succeed");
+        let mut round_trip = raw.clone();
+        decode_members(
+            &mut round_trip,
+            &merges,
+            &PayloadLimits::default(),
+            NumberParsing::Baseline,
+            &mut Truncations::default(),
+        )
+        .expect("decoding should succeed");
+        assert_eq!(round_trip, edited);
+    }
+}
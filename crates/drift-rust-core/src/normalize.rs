@@ -1,25 +1,235 @@
-use serde_json::Value as JsonValue;
+use serde::Deserialize;
+use serde_json::{Number as JsonNumber, Value as JsonValue};
 
 use crate::error::{CoreError, CoreResult};
+use crate::numbers::{lossless_literal, parse_literals, read_literal, to_json_string};
+
+/// Pre-hash cleanup applied on top of the JSON round trip, so every SDK
+/// produces the same normalized payload without re-implementing it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NormalizeOptions {
+    /// Remove object members whose value is `null`. Array elements are kept so
+    /// positions stay stable.
+    pub drop_nulls: bool,
+    /// Remove object members whose value is `{}` or `[]` after cleanup of
+    /// their children.
+    pub drop_empty: bool,
+    /// Trim leading and trailing whitespace from string values.
+    pub trim_strings: bool,
+    /// Field names (e.g. `headers`) whose object values get their keys
+    /// lowercased, at any depth.
+    pub lowercase_keys: Vec<String>,
+    /// Collapse integral floats (`1.0`, `1e3`, `-0.0`) into integers so that
+    /// SDKs whose runtimes keep or drop the float/int distinction hash alike.
+    /// Non-integral floats keep their shortest round-trip representation.
+    pub canonical_numbers: bool,
+    /// Keep number literals that no i64, u64 or f64 holds exactly (integers
+    /// beyond 64 bits, decimals that change as an f64) as written; the others
+    /// read as the correctly rounded f64. Kept literals are encoded as strings
+    /// in the protobuf Struct. JCS hashing (hash scheme `v2`) only has
+    /// doubles, so it rejects them instead of rounding them.
+    pub lossless_numbers: bool,
+}
+
+impl NormalizeOptions {
+    pub fn from_json(options_json: &str) -> CoreResult<Self> {
+        serde_json::from_str(options_json).map_err(|e| CoreError::InvalidJson(e.to_string()))
+    }
+
+    fn is_noop(&self) -> bool {
+        !self.drop_nulls
+            && !self.drop_empty
+            && !self.trim_strings
+            && self.lowercase_keys.is_empty()
+            && !self.canonical_numbers
+    }
+}
 
 pub fn normalize_json(payload_json: &str) -> CoreResult<String> {
-    let input = parse_json(payload_json)?;
-    let normalized = json_roundtrip_normalize(&input)?;
-    serde_json::to_string(&normalized).map_err(|e| CoreError::SerializationError(e.to_string()))
+    normalize_json_with_options(payload_json, &NormalizeOptions::default())
+}
+
+pub fn normalize_json_with_options(
+    payload_json: &str,
+    options: &NormalizeOptions,
+) -> CoreResult<String> {
+    let numbers = NumberParsing::Baseline.or_lossless(options);
+    let input = parse_payload(payload_json, numbers)?;
+    let normalized = normalize_value(input, options, numbers)?;
+    to_json_string(&normalized)
+}
+
+/// [`normalize_json_with_options`] for an already parsed value, without the
+/// text round trip.
+pub fn normalize_json_value(value: JsonValue, options: &NormalizeOptions) -> CoreResult<JsonValue> {
+    normalize_value(value, options, NumberParsing::Baseline.or_lossless(options))
 }
 
 pub(crate) fn parse_json(payload_json: &str) -> CoreResult<JsonValue> {
     serde_json::from_str(payload_json).map_err(|e| CoreError::InvalidJson(e.to_string()))
 }
 
-pub(crate) fn json_roundtrip_normalize(v: &JsonValue) -> CoreResult<JsonValue> {
-    let s = serde_json::to_string(v).map_err(|e| CoreError::SerializationError(e.to_string()))?;
-    serde_json::from_str(&s).map_err(|e| CoreError::SerializationError(e.to_string()))
+/// How number literals are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NumberParsing {
+    /// Plain serde_json, which is what v1 hashes were computed with: long
+    /// literals are truncated to 19 digits and scaled by a power of ten, so
+    /// the nearest f64 is not always the one picked.
+    Baseline,
+    /// The correctly rounded f64.
+    Exact,
+    /// Like `Exact`, but literals no i64, u64 or f64 holds are kept as
+    /// written (see [`crate::numbers`]).
+    Lossless,
+}
+
+impl NumberParsing {
+    /// `Lossless` when `options` ask for it, else `self`.
+    pub(crate) fn or_lossless(self, options: &NormalizeOptions) -> Self {
+        if options.lossless_numbers {
+            Self::Lossless
+        } else {
+            self
+        }
+    }
+}
+
+/// Parses a payload text, reading its number literals the way `numbers`
+/// says. Together with [`normalize_value`] this matches parsing, serializing
+/// and parsing again.
+pub(crate) fn parse_payload(payload_json: &str, numbers: NumberParsing) -> CoreResult<JsonValue> {
+    match numbers {
+        NumberParsing::Baseline => parse_json(payload_json),
+        _ => parse_literals(payload_json, numbers),
+    }
+}
+
+/// Reads the numbers of `value` the way `numbers` says, as if it had been
+/// serialized and parsed again: floats go through their shortest text for
+/// `Baseline` and lossless numbers are read from their literals.
+pub(crate) fn read_numbers(value: &mut JsonValue, numbers: NumberParsing) -> CoreResult<()> {
+    if let Some(literal) = lossless_literal(value) {
+        *value = read_literal(literal, numbers)
+            .ok_or_else(|| CoreError::InvalidJson(format!("number out of range: {literal}")))?;
+        return Ok(());
+    }
+    match value {
+        JsonValue::Number(n) if numbers == NumberParsing::Baseline => {
+            if let Some(f) = n.as_f64().filter(|_| n.is_f64()) {
+                // The shortest text of a finite f64 is always a valid literal.
+                if let Ok(reread) = serde_json::from_str(ryu::Buffer::new().format_finite(f)) {
+                    *n = reread;
+                }
+            }
+            Ok(())
+        }
+        JsonValue::Array(arr) => arr
+            .iter_mut()
+            .try_for_each(|child| read_numbers(child, numbers)),
+        JsonValue::Object(map) => map
+            .values_mut()
+            .try_for_each(|child| read_numbers(child, numbers)),
+        _ => Ok(()),
+    }
+}
+
+/// Reads numbers (see [`read_numbers`]) and applies the cleanup profile, in
+/// place.
+///
+/// This yields the same value a serialize/parse round trip would: maps are
+/// already key-sorted and strings are stored unescaped, so numbers are the
+/// only leaves whose representation can change.
+pub(crate) fn normalize_value(
+    v: JsonValue,
+    options: &NormalizeOptions,
+    numbers: NumberParsing,
+) -> CoreResult<JsonValue> {
+    let mut normalized = v;
+    read_numbers(&mut normalized, numbers)?;
+    Ok(apply_normalize_options(normalized, options))
+}
+
+fn apply_normalize_options(v: JsonValue, options: &NormalizeOptions) -> JsonValue {
+    if options.is_noop() {
+        return v;
+    }
+    clean_value(v, options, false)
+}
+
+fn clean_value(v: JsonValue, options: &NormalizeOptions, lowercase_keys: bool) -> JsonValue {
+    match v {
+        JsonValue::String(s) if options.trim_strings => {
+            let trimmed = s.trim();
+            if trimmed.len() == s.len() {
+                JsonValue::String(s)
+            } else {
+                JsonValue::String(trimmed.to_string())
+            }
+        }
+        JsonValue::Number(n) if options.canonical_numbers => JsonValue::Number(canonical_number(n)),
+        other if lossless_literal(&other).is_some() => other,
+        JsonValue::Array(arr) => JsonValue::Array(
+            arr.into_iter()
+                .map(|child| clean_value(child, options, false))
+                .collect(),
+        ),
+        JsonValue::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (k, child) in map {
+                let child_lowercase = options.lowercase_keys.contains(&k);
+                let child = clean_value(child, options, child_lowercase);
+                if options.drop_nulls && child.is_null() {
+                    continue;
+                }
+                if options.drop_empty && is_empty_container(&child) {
+                    continue;
+                }
+                let key = if lowercase_keys { k.to_lowercase() } else { k };
+                out.insert(key, child);
+            }
+            JsonValue::Object(out)
+        }
+        other => other,
+    }
+}
+
+fn canonical_number(n: JsonNumber) -> JsonNumber {
+    if !n.is_f64() {
+        return n;
+    }
+    let Some(f) = n.as_f64() else {
+        return n;
+    };
+    if f.fract() != 0.0 {
+        return n;
+    }
+    // `i64::MAX as f64` and `u64::MAX as f64` round up to 2^63 and 2^64, so the
+    // upper bounds are exclusive.
+    if f >= i64::MIN as f64 && f < i64::MAX as f64 {
+        (f as i64).into()
+    } else if f > 0.0 && f < u64::MAX as f64 {
+        (f as u64).into()
+    } else {
+        n
+    }
+}
+
+fn is_empty_container(v: &JsonValue) -> bool {
+    match v {
+        JsonValue::Array(arr) => arr.is_empty(),
+        JsonValue::Object(map) => map.is_empty(),
+        _ => false,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::payload::{
+        process_export_payload_value_with_options, process_export_payload_with_options,
+    };
+    use crate::types::ExportPayloadOptions;
 
     #[test]
     fn normalize_json_compacts_valid_json() {
@@ -34,4 +244,149 @@ mod tests {
         let err = normalize_json("{not-valid-json").expect_err("invalid json should fail");
         assert!(matches!(err, CoreError::InvalidJson(_)));
     }
+
+    #[test]
+    fn normalize_json_coerces_number_literals_by_default() {
+        let normalized = normalize_json(r#"[1e3,1.50,-0,9223372036854775808]"#)
+            .expect("normalization should succeed");
+
+        assert_eq!(normalized, "[1000.0,1.5,-0.0,9223372036854775808]");
+    }
+
+    #[test]
+    fn default_number_reading_matches_plain_serde_json() {
+        // Outputs of serde_json without `arbitrary_precision`, which reads
+        // long literals inexactly.
+        let normalized = normalize_json(
+            r#"{"s":"-0 \"-0","e":[1e-0,-0],"f":[2.2250738585072011e-308,1.00000000000000011102230246251565404236316680908203125]}"#,
+        )
+        .expect("normalization should succeed");
+        assert_eq!(
+            normalized,
+            r#"{"e":[1.0,-0.0],"f":[2.2250738585072014e-308,1.0000000000000002],"s":"-0 \"-0"}"#
+        );
+
+        let exact = parse_payload(
+            "[1.00000000000000011102230246251565404236316680908203125]",
+            NumberParsing::Exact,
+        )
+        .expect("payload should parse");
+        assert_eq!(exact, serde_json::json!([1.0]));
+    }
+
+    #[test]
+    fn normalize_value_matches_the_serialize_parse_round_trip_output() {
+        // Expected output recorded from the serialize/parse round trip that
+        // normalization used before it worked in place.
+        let input = parse_json(
+            r#"{"z":[1e3,1.50,-0,{"k":"\u00e9\n"}],"a":{"b":null,"c":18446744073709551615},"f":0.1,"g":1.7976931348623157e308,"h":5e-324}"#,
+        )
+        .expect("input should parse");
+        let normalized =
+            normalize_value(input, &NormalizeOptions::default(), NumberParsing::Baseline)
+                .expect("normalization should work");
+
+        assert_eq!(
+            to_json_string(&normalized).expect("value should serialize"),
+            r#"{"a":{"b":null,"c":18446744073709551615},"f":0.1,"g":1.7976931348623157e+308,"h":5e-324,"z":[1000.0,1.5,-0.0,{"k":"é\n"}]}"#
+        );
+    }
+
+    #[test]
+    fn normalize_json_with_lossless_numbers_keeps_literals() {
+        let input =
+            r#"{"id":123456789012345678901234567890,"amount":0.10000000000000000001,"n":1.0}"#;
+        let options = NormalizeOptions {
+            lossless_numbers: true,
+            canonical_numbers: true,
+            ..Default::default()
+        };
+        let normalized =
+            normalize_json_with_options(input, &options).expect("normalization should succeed");
+
+        assert_eq!(
+            normalized,
+            r#"{"amount":0.10000000000000000001,"id":123456789012345678901234567890,"n":1}"#
+        );
+    }
+
+    #[test]
+    fn normalize_json_with_options_drops_nulls_and_empty_containers() {
+        let input = r#"{"a":null,"b":{"c":null},"d":[],"e":[null,1],"f":"x"}"#;
+        let options = NormalizeOptions::from_json(r#"{"drop_nulls":true,"drop_empty":true}"#)
+            .expect("options should parse");
+        let normalized =
+            normalize_json_with_options(input, &options).expect("normalization should succeed");
+
+        assert_eq!(normalized, r#"{"e":[null,1],"f":"x"}"#);
+    }
+
+    #[test]
+    fn normalize_json_with_options_trims_strings_and_lowercases_selected_keys() {
+        let input = r#"{"headers":{"Content-Type":" text/plain "},"Other":{"Keep":"  v"}}"#;
+        let options = NormalizeOptions {
+            trim_strings: true,
+            lowercase_keys: vec!["headers".to_string()],
+            ..Default::default()
+        };
+        let normalized =
+            normalize_json_with_options(input, &options).expect("normalization should succeed");
+
+        assert_eq!(
+            normalized,
+            r#"{"Other":{"Keep":"v"},"headers":{"content-type":"text/plain"}}"#
+        );
+    }
+
+    #[test]
+    fn normalize_json_with_canonical_numbers_collapses_integral_floats() {
+        let input = r#"{"a":1.0,"b":1e3,"c":-0.0,"d":0.5,"e":1e21,"f":[2.0,3]}"#;
+        let options = NormalizeOptions {
+            canonical_numbers: true,
+            ..Default::default()
+        };
+        let normalized =
+            normalize_json_with_options(input, &options).expect("normalization should succeed");
+
+        assert_eq!(
+            normalized,
+            r#"{"a":1,"b":1000,"c":0,"d":0.5,"e":1e+21,"f":[2,3]}"#
+        );
+    }
+
+    #[test]
+    fn normalize_options_reject_invalid_json() {
+        let err = NormalizeOptions::from_json(r#"{"drop_nulls":"yes"}"#)
+            .expect_err("invalid options should fail");
+        assert!(matches!(err, CoreError::InvalidJson(_)));
+    }
+
+    #[test]
+    fn canonical_numbers_read_the_same_from_values_and_text() {
+        let options =
+            ExportPayloadOptions::from_json(r#"{"normalize":{"canonical_numbers":true}}"#)
+                .expect("options should parse");
+        let value_result = process_export_payload_value_with_options(
+            &serde_json::json!({"n": 1.0, "m": [2.0, 0.25]}),
+            None,
+            &options,
+        )
+        .expect("processing should work");
+        let string_result =
+            process_export_payload_with_options(r#"{"n":1,"m":[2,0.25]}"#, None, &options)
+                .expect("processing should work");
+
+        assert_eq!(
+            value_result.normalized_value,
+            serde_json::json!({"n": 1, "m": [2, 0.25]})
+        );
+        assert_eq!(
+            value_result.decoded_value_hash,
+            string_result.decoded_value_hash
+        );
+        assert_eq!(
+            value_result.decoded_schema_hash,
+            string_result.decoded_schema_hash
+        );
+    }
 }
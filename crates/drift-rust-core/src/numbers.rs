@@ -0,0 +1,323 @@
+//! Number literals beyond serde_json's default reading.
+//!
+//! serde_json is built without `arbitrary_precision`, so a `Number` is an
+//! i64, u64 or f64 and the default parser reads literals the way v1 hashes
+//! were computed. Hash scheme `v2` and `lossless_numbers` read every literal
+//! here instead, from its text. A literal none of the three types holds
+//! exactly (an integer wider than 64 bits, or a decimal that changes as an
+//! f64) is then kept as the one-member object `{"$drift::number":
+//! "<literal>"}`. Every walk over payload values treats that object as the
+//! number it spells: it serializes and hashes as the literal, infers as a
+//! number schema and goes into the protobuf Struct as a string. An input
+//! object of exactly that shape reads the same way.
+
+use serde::ser::{Error as _, Serialize, Serializer};
+use serde_json::value::RawValue;
+use serde_json::{Number as JsonNumber, Value as JsonValue};
+use std::collections::BTreeMap;
+
+use crate::error::{CoreError, CoreResult};
+use crate::limits::MAX_NESTING;
+use crate::normalize::NumberParsing;
+
+const LOSSLESS_KEY: &str = "$drift::number";
+
+/// The literal of a lossless number, or `None` when `value` is not one.
+pub fn lossless_literal(value: &JsonValue) -> Option<&str> {
+    let JsonValue::Object(map) = value else {
+        return None;
+    };
+    if map.len() != 1 {
+        return None;
+    }
+    match map.get(LOSSLESS_KEY) {
+        Some(JsonValue::String(literal)) if is_number_literal(literal) => Some(literal),
+        _ => None,
+    }
+}
+
+/// The value of a JSON number literal: a plain number when i64, u64 or f64
+/// holds it exactly, else a lossless number. `None` when `literal` is not a
+/// JSON number.
+pub fn number_from_literal(literal: &str) -> Option<JsonValue> {
+    is_number_literal(literal)
+        .then(|| read_literal(literal, NumberParsing::Lossless))
+        .flatten()
+}
+
+fn is_number_literal(s: &str) -> bool {
+    s.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+        && serde_json::from_str::<&RawValue>(s).is_ok_and(|raw| raw.get().len() == s.len())
+}
+
+/// Reads a valid number literal the way `numbers` says. `None` when it is
+/// out of f64 range and cannot be kept as written.
+pub(crate) fn read_literal(literal: &str, numbers: NumberParsing) -> Option<JsonValue> {
+    if numbers == NumberParsing::Baseline {
+        return serde_json::from_str(literal).ok().map(JsonValue::Number);
+    }
+    // Like serde_json, `-0` reads as the float -0.0.
+    if !literal.contains(['.', 'e', 'E']) && literal != "-0" {
+        if let Ok(u) = literal.parse::<u64>() {
+            return Some(u.into());
+        }
+        if let Ok(i) = literal.parse::<i64>() {
+            return Some(i.into());
+        }
+    }
+    let f = literal.parse::<f64>().ok().filter(|f| f.is_finite());
+    if numbers == NumberParsing::Lossless && !f.is_some_and(|f| same_decimal(literal, f)) {
+        let mut map = serde_json::Map::new();
+        map.insert(LOSSLESS_KEY.to_string(), literal.into());
+        return Some(JsonValue::Object(map));
+    }
+    f.and_then(JsonNumber::from_f64).map(JsonValue::Number)
+}
+
+/// Parses JSON text reading every number literal with [`read_literal`].
+///
+/// The document is checked by serde_json as a whole first, so syntax errors
+/// keep their positions; containers are then split into raw members level by
+/// level, which scans nested text once per enclosing container.
+pub(crate) fn parse_literals(json: &str, numbers: NumberParsing) -> CoreResult<JsonValue> {
+    let raw: &RawValue =
+        serde_json::from_str(json).map_err(|e| CoreError::InvalidJson(e.to_string()))?;
+    read_raw(json, raw, numbers, 0)
+}
+
+fn read_raw(
+    json: &str,
+    raw: &RawValue,
+    numbers: NumberParsing,
+    depth: usize,
+) -> CoreResult<JsonValue> {
+    let text = raw.get();
+    let invalid = |e: serde_json::Error| CoreError::InvalidJson(e.to_string());
+    match text.as_bytes()[0] {
+        b'[' | b'{' if depth >= MAX_NESTING => {
+            Err(error_at(json, text, 1, "recursion limit exceeded"))
+        }
+        b'[' => {
+            let items: Vec<&RawValue> = serde_json::from_str(text).map_err(invalid)?;
+            items
+                .into_iter()
+                .map(|item| read_raw(json, item, numbers, depth + 1))
+                .collect::<CoreResult<_>>()
+                .map(JsonValue::Array)
+        }
+        b'{' => {
+            let members: BTreeMap<String, &RawValue> =
+                serde_json::from_str(text).map_err(invalid)?;
+            members
+                .into_iter()
+                .map(|(k, v)| Ok((k, read_raw(json, v, numbers, depth + 1)?)))
+                .collect::<CoreResult<_>>()
+                .map(JsonValue::Object)
+        }
+        b'-' | b'0'..=b'9' => read_literal(text, numbers)
+            .ok_or_else(|| error_at(json, text, text.len(), "number out of range")),
+        _ => serde_json::from_str(text).map_err(invalid),
+    }
+}
+
+/// `message` at the position serde_json would report: the `len`th byte of
+/// `text`, a slice of `json`.
+fn error_at(json: &str, text: &str, len: usize, message: &str) -> CoreError {
+    let offset = (text.as_ptr() as usize).saturating_sub(json.as_ptr() as usize);
+    let before = &json[..(offset + len).min(json.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+    CoreError::InvalidJson(format!("{message} at line {line} column {column}"))
+}
+
+/// Serializes the wrapped value with lossless numbers written as their
+/// literals.
+pub(crate) struct WithLiterals<'a>(pub(crate) &'a JsonValue);
+
+impl Serialize for WithLiterals<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(literal) = lossless_literal(self.0) {
+            let raw: &RawValue = serde_json::from_str(literal).map_err(S::Error::custom)?;
+            return raw.serialize(serializer);
+        }
+        match self.0 {
+            JsonValue::Array(arr) => serializer.collect_seq(arr.iter().map(WithLiterals)),
+            JsonValue::Object(map) => {
+                serializer.collect_map(map.iter().map(|(k, v)| (k, WithLiterals(v))))
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+/// Whether `value` is a number an f64 (a protobuf `NumberValue`) does not
+/// hold exactly: a lossless number or, with `lossless_numbers`, an integer
+/// that would round.
+pub(crate) fn loses_digits_as_f64(value: &JsonValue, lossless_numbers: bool) -> bool {
+    match value {
+        JsonValue::Number(n) if lossless_numbers => {
+            if let Some(i) = n.as_i64() {
+                i as f64 as i128 != i128::from(i)
+            } else if let Some(u) = n.as_u64() {
+                u as f64 as u128 != u128::from(u)
+            } else {
+                false
+            }
+        }
+        _ => lossless_literal(value).is_some(),
+    }
+}
+
+/// Compact JSON of a payload value, lossless numbers included.
+pub(crate) fn to_json_string(v: &JsonValue) -> CoreResult<String> {
+    serde_json::to_string(&WithLiterals(v))
+        .map_err(|e| CoreError::SerializationError(e.to_string()))
+}
+
+/// Whether `literal` denotes exactly `f`, i.e. the same decimal as the
+/// shortest representation of `f`.
+fn same_decimal(literal: &str, f: f64) -> bool {
+    decimal_key(literal) == decimal_key(ryu::Buffer::new().format_finite(f))
+}
+
+/// Reduces a JSON number literal to `(negative, significant digits, exponent)`
+/// with `value = 0.digits * 10^exponent`, so equal decimals compare equal
+/// regardless of trailing zeros or exponent notation.
+fn decimal_key(literal: &str) -> (bool, String, i64) {
+    let (negative, unsigned) = match literal.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, literal),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i64>().unwrap_or(0)),
+        None => (unsigned, 0),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all_digits = format!("{int_part}{frac_part}");
+    let significant = all_digits.trim_start_matches('0');
+    let leading_zeros = (all_digits.len() - significant.len()) as i64;
+    let significant = significant.trim_end_matches('0');
+    if significant.is_empty() {
+        return (false, String::new(), 0);
+    }
+    (
+        negative,
+        significant.to_string(),
+        int_part.len() as i64 + exponent - leading_zeros,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_read_exactly_or_stay_as_written() {
+        let exact = parse_literals(
+            "[1.00000000000000011102230246251565404236316680908203125,-0,18446744073709551615]",
+            NumberParsing::Exact,
+        )
+        .expect("payload should parse");
+        assert_eq!(
+            to_json_string(&exact).expect("value should serialize"),
+            "[1.0,-0.0,18446744073709551615]"
+        );
+
+        let lossless = parse_literals(
+            r#"{"id":123456789012345678901234567890,"a":[0.10000000000000000001,1e400,1.50]}"#,
+            NumberParsing::Lossless,
+        )
+        .expect("payload should parse");
+        assert_eq!(
+            lossless_literal(&lossless["id"]),
+            Some("123456789012345678901234567890")
+        );
+        assert_eq!(
+            to_json_string(&lossless).expect("value should serialize"),
+            r#"{"a":[0.10000000000000000001,1e400,1.5],"id":123456789012345678901234567890}"#
+        );
+    }
+
+    #[test]
+    fn literal_errors_keep_serde_json_positions() {
+        let message = |json: &str, numbers| match parse_literals(json, numbers) {
+            Err(CoreError::InvalidJson(message)) => message,
+            other => panic!("expected invalid json, got {other:?}"),
+        };
+        let stock = |json: &str| {
+            serde_json::from_str::<JsonValue>(json)
+                .expect_err("payload should fail")
+                .to_string()
+        };
+
+        let out_of_range = "{\"a\":\n [1, 1e400]}";
+        assert_eq!(
+            message(out_of_range, NumberParsing::Exact),
+            stock(out_of_range)
+        );
+        let deep = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert_eq!(message(&deep, NumberParsing::Lossless), stock(&deep));
+        let nested = format!("{}{}", "[".repeat(127), "]".repeat(127));
+        assert!(parse_literals(&nested, NumberParsing::Exact).is_ok());
+    }
+
+    #[test]
+    fn only_literals_a_64_bit_type_loses_become_lossless_numbers() {
+        let kept = |literal: &str| {
+            number_from_literal(literal)
+                .expect("literal should read")
+                .is_object()
+        };
+        for literal in ["0.1", "1.50", "2.5e-3", "9007199254740993", "-0", "1e20"] {
+            assert!(!kept(literal), "{literal}");
+        }
+        for literal in [
+            "9007199254740993.5",
+            "0.10000000000000000001",
+            "18446744073709551616",
+        ] {
+            assert!(kept(literal), "{literal}");
+        }
+        assert_eq!(number_from_literal("42"), Some(serde_json::json!(42)));
+
+        assert_eq!(number_from_literal("1.5 "), None);
+        assert_eq!(number_from_literal("NaN"), None);
+
+        assert_eq!(
+            lossless_literal(&serde_json::json!({"$drift::number": "1e999"})),
+            Some("1e999")
+        );
+        assert_eq!(
+            lossless_literal(&serde_json::json!({"$drift::number": "]"})),
+            None
+        );
+        assert_eq!(
+            lossless_literal(&serde_json::json!({"$drift::number": "1", "b": 2})),
+            None
+        );
+    }
+
+    #[test]
+    fn payload_walks_treat_lossless_numbers_as_numbers() {
+        let options = crate::ExportPayloadOptions::from_json(
+            r#"{"normalize":{"lossless_numbers":true},"limits":{"max_string_len":3,"max_depth":2},
+                "detectors":{"phone":true,"card_number":true},"path_hash_depth":3}"#,
+        )
+        .expect("options should parse");
+        let payload = r#"{"a":{"id":4111111111111111111111},"b":[12345678901234567890.5]}"#;
+        let result = crate::process_export_payload_with_options(payload, None, &options)
+            .expect("processing should work");
+
+        assert_eq!(result.normalized_json, payload);
+        assert_eq!(result.decoded_json, payload);
+        assert!(result.redactions.is_empty());
+        assert!(result.path_hashes.contains_key("a.id"));
+        assert!(!result.path_hashes.keys().any(|path| path.contains("drift")));
+        let schema: JsonValue =
+            serde_json::from_str(&result.decoded_schema_json).expect("schema should parse");
+        assert_eq!(
+            schema["properties"]["a"]["properties"]["id"],
+            serde_json::json!({"type": 1, "properties": {}, "lossless_number": true})
+        );
+    }
+}
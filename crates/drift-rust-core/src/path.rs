@@ -0,0 +1,339 @@
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::fmt::{Display, Formatter};
+
+use crate::error::{CoreError, CoreResult};
+use crate::numbers::lossless_literal;
+
+/// A location pattern inside a JSON value.
+///
+/// Two spellings are accepted:
+/// - dotted paths such as `headers.authorization`, `body.users[*].ssn`,
+///   `items[0]` or `headers["x.trace-id"]` (an optional leading `$` is ignored)
+/// - JSON Pointers such as `/body/users/*/ssn`
+///
+/// `*` matches every member of an object or element of an array.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct JsonPath {
+    segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum PathSegment {
+    /// Object member; also matches the array element at that index when the
+    /// key is numeric (JSON Pointer semantics).
+    Key(String),
+    /// Array element only.
+    Index(usize),
+    Wildcard,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> CoreResult<Self> {
+        let segments = if path.starts_with('/') {
+            parse_pointer(path)
+        } else {
+            parse_dotted(path)?
+        };
+        Ok(Self { segments })
+    }
+
+    pub(crate) fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+}
+
+impl TryFrom<String> for JsonPath {
+    type Error = CoreError;
+
+    fn try_from(path: String) -> CoreResult<Self> {
+        Self::parse(&path)
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(k) => push_key(&mut rendered, k),
+                PathSegment::Index(i) => push_index(&mut rendered, *i),
+                PathSegment::Wildcard => {
+                    rendered.push_str(if rendered.is_empty() { "*" } else { ".*" })
+                }
+            }
+        }
+        f.write_str(&rendered)
+    }
+}
+
+fn parse_pointer(path: &str) -> Vec<PathSegment> {
+    path[1..]
+        .split('/')
+        .map(|raw| {
+            let token = raw.replace("~1", "/").replace("~0", "~");
+            if token == "*" {
+                PathSegment::Wildcard
+            } else {
+                PathSegment::Key(token)
+            }
+        })
+        .collect()
+}
+
+fn parse_dotted(path: &str) -> CoreResult<Vec<PathSegment>> {
+    let invalid = |reason: &str| CoreError::InvalidJson(format!("invalid path {path:?}: {reason}"));
+    let mut rest = match path.strip_prefix('$') {
+        Some(r) => r.strip_prefix('.').unwrap_or(r),
+        None => path,
+    };
+    let mut segments = Vec::new();
+    let mut first = true;
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            if let Some(after) = inner.strip_prefix("*]") {
+                segments.push(PathSegment::Wildcard);
+                rest = after;
+            } else if inner.starts_with('"') {
+                let mut stream = serde_json::Deserializer::from_str(inner).into_iter::<String>();
+                let key = stream
+                    .next()
+                    .ok_or_else(|| invalid("missing quoted key"))?
+                    .map_err(|e| invalid(&e.to_string()))?;
+                rest = inner[stream.byte_offset()..]
+                    .strip_prefix(']')
+                    .ok_or_else(|| invalid("expected ']' after quoted key"))?;
+                segments.push(PathSegment::Key(key));
+            } else {
+                let (index, after) = inner
+                    .split_once(']')
+                    .ok_or_else(|| invalid("unterminated '['"))?;
+                let index = index
+                    .parse::<usize>()
+                    .map_err(|_| invalid("expected an index, '*' or a quoted key"))?;
+                segments.push(PathSegment::Index(index));
+                rest = after;
+            }
+        } else {
+            let after = if first {
+                rest
+            } else {
+                rest.strip_prefix('.')
+                    .ok_or_else(|| invalid("expected '.' or '['"))?
+            };
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return Err(invalid("empty key"));
+            }
+            segments.push(if key == "*" {
+                PathSegment::Wildcard
+            } else {
+                PathSegment::Key(key.to_string())
+            });
+            rest = &after[end..];
+        }
+        first = false;
+    }
+    Ok(segments)
+}
+
+/// Appends an object member to a rendered path, quoting keys that would not
+/// parse back as a plain dotted segment.
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    let plain = !key.is_empty()
+        && key != "*"
+        && !key.starts_with('$')
+        && !key.contains(['.', '[', ']', '"']);
+    if plain {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    } else {
+        path.push('[');
+        path.push_str(&JsonValue::String(key.to_string()).to_string());
+        path.push(']');
+    }
+}
+
+pub(crate) fn push_index(path: &mut String, index: usize) {
+    path.push('[');
+    path.push_str(&index.to_string());
+    path.push(']');
+}
+
+/// Calls `f` on every value matched by `segments`.
+pub(crate) fn for_each_match_mut(
+    value: &mut JsonValue,
+    segments: &[PathSegment],
+    f: &mut dyn FnMut(&mut JsonValue),
+) {
+    for_each_match_with_path_mut(value, segments, &mut Vec::new(), &mut |_, v| f(v));
+}
+
+/// Like [`for_each_match_mut`], also passing the concrete location of each
+/// match as `Key`/`Index` segments, appended to `path`.
+pub(crate) fn for_each_match_with_path_mut(
+    value: &mut JsonValue,
+    segments: &[PathSegment],
+    path: &mut Vec<PathSegment>,
+    f: &mut dyn FnMut(&[PathSegment], &mut JsonValue),
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        f(path, value);
+        return;
+    };
+    // A lossless number is a leaf, whatever its representation.
+    if lossless_literal(value).is_some() {
+        return;
+    }
+    let mut descend = |step: PathSegment, child: &mut JsonValue| {
+        path.push(step);
+        for_each_match_with_path_mut(child, rest, path, f);
+        path.pop();
+    };
+    match (segment, value) {
+        (PathSegment::Wildcard, JsonValue::Object(map)) => {
+            for (k, child) in map.iter_mut() {
+                descend(PathSegment::Key(k.clone()), child);
+            }
+        }
+        (PathSegment::Wildcard, JsonValue::Array(arr)) => {
+            for (i, child) in arr.iter_mut().enumerate() {
+                descend(PathSegment::Index(i), child);
+            }
+        }
+        (PathSegment::Key(k), JsonValue::Object(map)) => {
+            if let Some(child) = map.get_mut(k) {
+                descend(PathSegment::Key(k.clone()), child);
+            }
+        }
+        (PathSegment::Key(k), JsonValue::Array(arr)) => {
+            if let Some((i, child)) = k
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| Some((i, arr.get_mut(i)?)))
+            {
+                descend(PathSegment::Index(i), child);
+            }
+        }
+        (PathSegment::Index(i), JsonValue::Array(arr)) => {
+            if let Some(child) = arr.get_mut(*i) {
+                descend(PathSegment::Index(*i), child);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The value at a concrete location produced by
+/// [`for_each_match_with_path_mut`].
+pub(crate) fn value_at<'a>(value: &'a JsonValue, path: &[PathSegment]) -> Option<&'a JsonValue> {
+    path.iter()
+        .try_fold(value, |v, segment| match (segment, v) {
+            (PathSegment::Key(k), JsonValue::Object(map)) => map.get(k),
+            (PathSegment::Index(i), JsonValue::Array(arr)) => arr.get(*i),
+            _ => None,
+        })
+}
+
+/// Removes every object member or array element matched by `segments`.
+/// Returns whether anything was removed. The root itself cannot be removed.
+pub(crate) fn remove_matches(value: &mut JsonValue, segments: &[PathSegment]) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut removed = false;
+    for_each_match_mut(value, parents, &mut |parent| {
+        if lossless_literal(parent).is_some() {
+            return;
+        }
+        match (last, parent) {
+            (PathSegment::Wildcard, JsonValue::Object(map)) => {
+                removed |= !map.is_empty();
+                map.clear();
+            }
+            (PathSegment::Wildcard, JsonValue::Array(arr)) => {
+                removed |= !arr.is_empty();
+                arr.clear();
+            }
+            (PathSegment::Key(k), JsonValue::Object(map)) => {
+                removed |= map.remove(k).is_some();
+            }
+            (PathSegment::Key(k), JsonValue::Array(arr)) => {
+                if let Some(i) = k.parse::<usize>().ok().filter(|i| *i < arr.len()) {
+                    arr.remove(i);
+                    removed = true;
+                }
+            }
+            (PathSegment::Index(i), JsonValue::Array(arr)) if *i < arr.len() => {
+                arr.remove(*i);
+                removed = true;
+            }
+            _ => {}
+        }
+    });
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dotted_and_pointer_paths_to_the_same_segments() {
+        let dotted = JsonPath::parse("body.users[*].ssn").expect("dotted path should parse");
+        let pointer = JsonPath::parse("/body/users/*/ssn").expect("pointer should parse");
+
+        assert_eq!(dotted, pointer);
+        assert_eq!(
+            JsonPath::parse(r#"$.headers["x.trace]id"][0]"#)
+                .expect("quoted path should parse")
+                .segments(),
+            &[
+                PathSegment::Key("headers".to_string()),
+                PathSegment::Key("x.trace]id".to_string()),
+                PathSegment::Index(0),
+            ]
+        );
+        assert!(JsonPath::parse("a..b").is_err());
+        assert!(JsonPath::parse("a[x]").is_err());
+    }
+
+    #[test]
+    fn rendered_paths_parse_back() {
+        let mut rendered = String::new();
+        push_key(&mut rendered, "body");
+        push_key(&mut rendered, "x.y");
+        push_index(&mut rendered, 2);
+        push_key(&mut rendered, "ssn");
+
+        assert_eq!(rendered, r#"body["x.y"][2].ssn"#);
+        assert_eq!(
+            JsonPath::parse(&rendered)
+                .expect("rendered path should parse")
+                .to_string(),
+            rendered
+        );
+    }
+
+    #[test]
+    fn wildcard_matches_and_removals_cover_every_element() {
+        let mut value = serde_json::json!({
+            "users": [{"ssn": "1", "name": "a"}, {"ssn": "2", "name": "b"}]
+        });
+        let path = JsonPath::parse("users[*].ssn").expect("path should parse");
+        let mut seen = Vec::new();
+        for_each_match_mut(&mut value, path.segments(), &mut |v| seen.push(v.clone()));
+
+        assert_eq!(seen, vec![serde_json::json!("1"), serde_json::json!("2")]);
+        assert!(remove_matches(&mut value, path.segments()));
+        assert_eq!(
+            value,
+            serde_json::json!({"users": [{"name": "a"}, {"name": "b"}]})
+        );
+    }
+}
@@ -1,37 +1,88 @@
 use serde_json::Value as JsonValue;
 
 use crate::error::{CoreError, CoreResult};
-use crate::normalize::parse_json;
+use crate::limits::{Truncations, check_nesting, limit_payload_text};
+use crate::normalize::parse_payload;
+use crate::numbers::to_json_string;
 use crate::schema;
-use crate::types::{ExportPayloadResult, ExportPayloadValueResult};
+use crate::types::{ExportPayloadOptions, ExportPayloadResult, ExportPayloadValueResult};
 
 pub fn process_export_payload(
     payload_json: &str,
     schema_merges_json: Option<&str>,
 ) -> CoreResult<ExportPayloadResult> {
-    let input = parse_json(payload_json)?;
-    let value_result = process_export_payload_value(&input, schema_merges_json)?;
-    let normalized_json = serde_json::to_string(&value_result.normalized_value)
-        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
-    let decoded_json = serde_json::to_string(&value_result.decoded_value)
-        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
+    process_export_payload_with_options(
+        payload_json,
+        schema_merges_json,
+        &ExportPayloadOptions::default(),
+    )
+}
+
+pub fn process_export_payload_with_options(
+    payload_json: &str,
+    schema_merges_json: Option<&str>,
+    options: &ExportPayloadOptions,
+) -> CoreResult<ExportPayloadResult> {
+    let value_result = process_export_payload_text(payload_json, schema_merges_json, options)?;
+    let normalized_json = to_json_string(&value_result.normalized_value)?;
+    let decoded_json = to_json_string(&value_result.decoded_value)?;
 
     Ok(ExportPayloadResult {
         normalized_json,
         decoded_json,
         decoded_value_hash: value_result.decoded_value_hash,
+        match_hash: value_result.match_hash,
+        importance_hash: value_result.importance_hash,
+        fingerprint: value_result.fingerprint,
+        path_hashes: value_result.path_hashes,
         decoded_schema_json: serde_json::to_string(&value_result.decoded_schema_value)
             .map_err(|e| CoreError::SerializationError(e.to_string()))?,
         decoded_schema_hash: value_result.decoded_schema_hash,
+        shape_hash: value_result.shape_hash,
+        hash_scheme: value_result.hash_scheme,
         protobuf_struct_bytes: value_result.protobuf_struct_bytes,
+        redactions: value_result.redactions,
     })
 }
 
+/// Limits and parses the payload text, then processes the value; the value
+/// half of [`process_export_payload_with_options`].
+pub(crate) fn process_export_payload_text(
+    payload_json: &str,
+    schema_merges_json: Option<&str>,
+    options: &ExportPayloadOptions,
+) -> CoreResult<ExportPayloadValueResult> {
+    let mut truncated = Truncations::default();
+    let payload_json = limit_payload_text(payload_json, &options.limits, &mut truncated)?;
+    let rules = options.hash_rules()?;
+    let input = parse_payload(&payload_json, rules.numbers)?;
+    check_nesting(&input, 0)?;
+    schema::process_export_payload_value(&input, schema_merges_json, options, truncated)
+}
+
 pub fn process_export_payload_value(
     payload_value: &JsonValue,
     schema_merges_json: Option<&str>,
 ) -> CoreResult<ExportPayloadValueResult> {
-    schema::process_export_payload_value(payload_value, schema_merges_json)
+    process_export_payload_value_with_options(
+        payload_value,
+        schema_merges_json,
+        &ExportPayloadOptions::default(),
+    )
+}
+
+pub fn process_export_payload_value_with_options(
+    payload_value: &JsonValue,
+    schema_merges_json: Option<&str>,
+    options: &ExportPayloadOptions,
+) -> CoreResult<ExportPayloadValueResult> {
+    check_nesting(payload_value, 0)?;
+    schema::process_export_payload_value(
+        payload_value,
+        schema_merges_json,
+        options,
+        Truncations::default(),
+    )
 }
 
 #[cfg(test)]
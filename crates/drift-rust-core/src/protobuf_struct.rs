@@ -4,11 +4,13 @@ use serde_json::Value as JsonValue;
 use std::collections::BTreeMap;
 
 use crate::error::CoreResult;
-use crate::normalize::{json_roundtrip_normalize, parse_json};
+use crate::normalize::{NormalizeOptions, NumberParsing, normalize_value, parse_payload};
+use crate::numbers::{loses_digits_as_f64, lossless_literal};
 
 pub fn object_to_protobuf_struct(payload_json: &str) -> CoreResult<Struct> {
-    let input = parse_json(payload_json)?;
-    let normalized = json_roundtrip_normalize(&input)?;
+    let options = NormalizeOptions::default();
+    let input = parse_payload(payload_json, NumberParsing::Baseline)?;
+    let normalized = normalize_value(input, &options, NumberParsing::Baseline)?;
     Ok(json_object_to_struct(&normalized))
 }
 
@@ -23,11 +25,18 @@ pub fn object_to_protobuf_struct_field_count(payload_json: &str) -> CoreResult<u
 }
 
 pub(crate) fn json_object_to_struct(v: &JsonValue) -> Struct {
+    json_object_to_struct_with(v, false)
+}
+
+/// Like [`json_object_to_struct`], but with `lossless_numbers` set numbers that
+/// would lose digits as an f64 are carried as their exact literal string.
+/// Lossless numbers are carried that way in any case.
+pub(crate) fn json_object_to_struct_with(v: &JsonValue, lossless_numbers: bool) -> Struct {
     match v {
-        JsonValue::Object(map) => {
+        JsonValue::Object(map) if lossless_literal(v).is_none() => {
             let mut fields = BTreeMap::new();
             for (k, child) in map {
-                fields.insert(k.clone(), json_to_protobuf_value(child));
+                fields.insert(k.clone(), json_to_protobuf_value(child, lossless_numbers));
             }
             Struct { fields }
         }
@@ -37,8 +46,13 @@ pub(crate) fn json_object_to_struct(v: &JsonValue) -> Struct {
     }
 }
 
-fn json_to_protobuf_value(v: &JsonValue) -> Value {
+fn json_to_protobuf_value(v: &JsonValue, lossless_numbers: bool) -> Value {
     match v {
+        _ if loses_digits_as_f64(v, lossless_numbers) => Value {
+            kind: Some(Kind::StringValue(
+                lossless_literal(v).map_or_else(|| v.to_string(), str::to_string),
+            )),
+        },
         JsonValue::Null => Value {
             kind: Some(Kind::NullValue(0)),
         },
@@ -53,13 +67,16 @@ fn json_to_protobuf_value(v: &JsonValue) -> Value {
         },
         JsonValue::Array(arr) => Value {
             kind: Some(Kind::ListValue(prost_types::ListValue {
-                values: arr.iter().map(json_to_protobuf_value).collect(),
+                values: arr
+                    .iter()
+                    .map(|child| json_to_protobuf_value(child, lossless_numbers))
+                    .collect(),
             })),
         },
         JsonValue::Object(map) => {
             let mut fields = BTreeMap::new();
             for (k, child) in map {
-                fields.insert(k.clone(), json_to_protobuf_value(child));
+                fields.insert(k.clone(), json_to_protobuf_value(child, lossless_numbers));
             }
             Value {
                 kind: Some(Kind::StructValue(Struct { fields })),
@@ -91,6 +108,27 @@ mod tests {
         assert!(s.fields.is_empty());
     }
 
+    #[test]
+    fn lossless_struct_encodes_imprecise_numbers_as_strings() {
+        let value: JsonValue =
+            serde_json::from_str(r#"{"id":9007199254740993,"small":42,"price":1.5}"#)
+                .expect("payload should parse");
+        let s = json_object_to_struct_with(&value, true);
+
+        assert!(matches!(
+            s.fields.get("id").and_then(|v| v.kind.as_ref()),
+            Some(Kind::StringValue(id)) if id == "9007199254740993"
+        ));
+        assert!(matches!(
+            s.fields.get("small").and_then(|v| v.kind.as_ref()),
+            Some(Kind::NumberValue(n)) if *n == 42.0
+        ));
+        assert!(matches!(
+            s.fields.get("price").and_then(|v| v.kind.as_ref()),
+            Some(Kind::NumberValue(n)) if *n == 1.5
+        ));
+    }
+
     #[test]
     fn object_to_protobuf_struct_field_count_matches_field_total() {
         let payload = r#"{"a":1,"b":2,"c":{"nested":3}}"#;
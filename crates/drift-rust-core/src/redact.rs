@@ -0,0 +1,243 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use sha2::Sha256;
+
+use crate::error::CoreResult;
+use crate::hash::{CanonicalForm, hash_json_value_deterministic, write_canonical};
+use crate::path::{JsonPath, PathSegment, for_each_match_mut, remove_matches};
+
+pub(crate) const REDACTED_PLACEHOLDER: &str = "<REDACTED>";
+
+/// Rewrites every value matched by `path` before anything is hashed or
+/// encoded.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactionRule {
+    pub path: JsonPath,
+    #[serde(default)]
+    pub action: RedactionAction,
+    /// Constant written by [`RedactionAction::Replace`]; `null` when omitted.
+    #[serde(default)]
+    pub value: JsonValue,
+    /// Secret for [`RedactionAction::Hash`]. When set, values become
+    /// `"hmac-sha256:<hex>"`, keyed with it, instead of a plain digest.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionAction {
+    /// Replace the value with `"<REDACTED>"`.
+    #[default]
+    Mask,
+    /// Drop the object member or array element.
+    Remove,
+    /// Replace the value with the rule's `value`.
+    Replace,
+    /// Replace the value with `"sha256:<hex>"` of its sorted-key JSON, so
+    /// equal secrets still compare equal across traces. The digest is
+    /// unsalted: anyone holding the trace can confirm a guess, and
+    /// low-entropy values (PINs, phone numbers, emails) can be recovered by
+    /// hashing candidates. Set the rule's `key` to get an HMAC instead.
+    Hash,
+}
+
+pub(crate) fn apply_redaction_rules(
+    value: &mut JsonValue,
+    rules: &[RedactionRule],
+) -> CoreResult<()> {
+    for rule in rules {
+        apply_rule(value, rule, rule.path.segments())?;
+    }
+    Ok(())
+}
+
+fn apply_rule(
+    value: &mut JsonValue,
+    rule: &RedactionRule,
+    segments: &[PathSegment],
+) -> CoreResult<()> {
+    match rule.action {
+        RedactionAction::Remove => {
+            remove_matches(value, segments);
+        }
+        RedactionAction::Mask => for_each_match_mut(value, segments, &mut |v| {
+            *v = JsonValue::String(REDACTED_PLACEHOLDER.to_string());
+        }),
+        RedactionAction::Replace => {
+            for_each_match_mut(value, segments, &mut |v| *v = rule.value.clone())
+        }
+        RedactionAction::Hash => {
+            let mut result = Ok(());
+            for_each_match_mut(value, segments, &mut |v| {
+                if result.is_err() {
+                    return;
+                }
+                let hashed = match &rule.key {
+                    Some(key) => keyed_hash(key.as_bytes(), v).map(|h| format!("hmac-sha256:{h}")),
+                    None => hash_json_value_deterministic(v).map(|h| format!("sha256:{h}")),
+                };
+                match hashed {
+                    Ok(hash) => *v = JsonValue::String(hash),
+                    Err(e) => result = Err(e),
+                }
+            });
+            result?;
+        }
+    }
+    Ok(())
+}
+
+/// HMAC-SHA256 of the sorted-key JSON of `v`.
+fn keyed_hash(key: &[u8], v: &JsonValue) -> CoreResult<String> {
+    let mut message = Vec::new();
+    write_canonical(v, CanonicalForm::Sorted, &mut message)?;
+    Ok(hmac_sha256(key, &message))
+}
+
+/// HMAC-SHA256, hex encoded.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::process_export_payload_value_with_options;
+    use crate::types::ExportPayloadOptions;
+
+    fn rules(rules_json: &str) -> Vec<RedactionRule> {
+        serde_json::from_str(rules_json).expect("rules should parse")
+    }
+
+    #[test]
+    fn applies_each_action_to_matching_paths() {
+        let mut value = serde_json::json!({
+            "headers": {"authorization": "Bearer abc", "cookie": "s=1", "host": "x"},
+            "users": [{"ssn": "123", "email": "a@b.c"}, {"ssn": "456"}],
+            "token": "t"
+        });
+        let rules = rules(
+            r#"[
+                {"path":"headers.authorization"},
+                {"path":"/headers/cookie","action":"remove"},
+                {"path":"users[*].ssn","action":"replace","value":"***"},
+                {"path":"token","action":"hash"}
+            ]"#,
+        );
+        apply_redaction_rules(&mut value, &rules).expect("redaction should succeed");
+
+        let token_hash =
+            hash_json_value_deterministic(&serde_json::json!("t")).expect("hashing should succeed");
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "headers": {"authorization": "<REDACTED>", "host": "x"},
+                "users": [{"ssn": "***", "email": "a@b.c"}, {"ssn": "***"}],
+                "token": format!("sha256:{token_hash}")
+            })
+        );
+    }
+
+    #[test]
+    fn keyed_hash_rules_write_an_hmac() {
+        // RFC 4231 test cases 2 and 6 (a key longer than the block).
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+
+        let mut value = serde_json::json!({"pin": "1234", "other": "1234"});
+        let rules = rules(
+            r#"[{"path":"pin","action":"hash","key":"k1"},{"path":"other","action":"hash","key":"k2"}]"#,
+        );
+        apply_redaction_rules(&mut value, &rules).expect("redaction should succeed");
+
+        assert_eq!(
+            value["pin"],
+            format!("hmac-sha256:{}", hmac_sha256(b"k1", br#""1234""#))
+        );
+        assert_ne!(value["pin"], value["other"]);
+    }
+
+    #[test]
+    fn invalid_rule_paths_are_rejected() {
+        let err = serde_json::from_str::<Vec<RedactionRule>>(r#"[{"path":"a[x]"}]"#)
+            .expect_err("invalid path should fail");
+        assert!(err.to_string().contains("invalid path"));
+    }
+
+    #[test]
+    fn redacts_before_hashing_and_encoding() {
+        use base64::Engine as _;
+
+        let options = ExportPayloadOptions::from_json(
+            r#"{"redaction":[
+                {"path":"headers.authorization"},
+                {"path":"body.password","action":"remove"}
+            ]}"#,
+        )
+        .expect("options should parse");
+        let body = base64::engine::general_purpose::STANDARD
+            .encode(r#"{"password":"hunter2","user":"u"}"#);
+        let payload = serde_json::json!({
+            "headers": {"authorization": "Bearer secret"},
+            "body": body
+        });
+        let merges = r#"{"body":{"encoding":1,"decoded_type":1}}"#;
+        let result = process_export_payload_value_with_options(&payload, Some(merges), &options)
+            .expect("processing should work");
+
+        assert_eq!(
+            result.decoded_value,
+            serde_json::json!({
+                "headers": {"authorization": "<REDACTED>"},
+                "body": {"user": "u"}
+            })
+        );
+        let raw_body = base64::engine::general_purpose::STANDARD
+            .decode(
+                result.normalized_value["body"]
+                    .as_str()
+                    .expect("body stays a string"),
+            )
+            .expect("body stays base64");
+        assert_eq!(raw_body, br#"{"user":"u"}"#);
+        let struct_bytes = String::from_utf8_lossy(&result.protobuf_struct_bytes);
+        assert!(!struct_bytes.contains("Bearer secret"));
+    }
+
+    #[test]
+    fn redacts_form_fields_and_writes_the_form_back() {
+        let options =
+            ExportPayloadOptions::from_json(r#"{"redaction":[{"path":"body.password"}]}"#)
+                .expect("options should parse");
+        let payload = serde_json::json!({"body": "user=u&password=hunter2&tag=a&tag=b"});
+        let merges = r#"{"body":{"decoded_type":12}}"#;
+        let result = process_export_payload_value_with_options(&payload, Some(merges), &options)
+            .expect("processing should work");
+
+        assert_eq!(
+            result.decoded_value,
+            serde_json::json!({"body": {"password": "<REDACTED>", "tag": ["a", "b"], "user": "u"}})
+        );
+        assert_eq!(
+            result.normalized_value,
+            serde_json::json!({"body": "password=%3CREDACTED%3E&tag=a&tag=b&user=u"})
+        );
+        assert_eq!(
+            result.decoded_schema_value["properties"]["body"]["properties"]["tag"]["type"],
+            serde_json::json!(7)
+        );
+    }
+}
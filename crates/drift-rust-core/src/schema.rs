@@ -1,113 +1,150 @@
-use base64::Engine as _;
 use prost::Message;
-use serde::Deserialize;
 use serde_json::Value as JsonValue;
-use std::collections::BTreeMap;
-
-use crate::error::{CoreError, CoreResult};
-use crate::hash::hash_json_value_deterministic;
-use crate::normalize::json_roundtrip_normalize;
-use crate::protobuf_struct::json_object_to_struct;
-use crate::types::ExportPayloadValueResult;
-
-#[derive(Debug, Deserialize)]
-struct MergeRule {
-    #[serde(default)]
-    encoding: Option<i32>,
-    #[serde(default)]
-    decoded_type: Option<i32>,
-    #[serde(default)]
-    match_importance: Option<f64>,
-}
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::detect::detect_and_redact;
+use crate::error::CoreResult;
+use crate::fingerprint::simhash_fingerprint;
+use crate::hash::{hash_json_value, hash_json_value_with_paths};
+use crate::limits::{Truncations, limit_value};
+use crate::merge::{SchemaMerge, decode_members, encode_members, parse_schema_merges};
+use crate::normalize::normalize_value;
+use crate::numbers::{loses_digits_as_f64, lossless_literal};
+use crate::path::{PathSegment, remove_matches};
+use crate::protobuf_struct::json_object_to_struct_with;
+use crate::redact::apply_redaction_rules;
+use crate::shape::shape_of;
+use crate::types::{ExportPayloadOptions, ExportPayloadValueResult};
+use crate::volatile::mask_volatile;
 
 pub(crate) fn process_export_payload_value(
     payload_value: &JsonValue,
     schema_merges_json: Option<&str>,
+    options: &ExportPayloadOptions,
+    mut truncated: Truncations,
 ) -> CoreResult<ExportPayloadValueResult> {
-    let normalized = json_roundtrip_normalize(payload_value)?;
-    let decoded = if let Some(merges_json) = schema_merges_json {
-        apply_schema_merges_top_level(&normalized, merges_json)?
+    let rules = options.hash_rules()?;
+    let lossless_numbers = rules.normalize.lossless_numbers;
+    let payload_value = limit_value(payload_value, &options.limits, &mut truncated)?;
+    let mut truncated = truncated.normalized(&rules.normalize);
+    let mut normalized =
+        normalize_value(payload_value.into_owned(), &rules.normalize, rules.numbers)?;
+    let merges = parse_schema_merges(schema_merges_json, &normalized)?;
+    let mut decoded = normalized.clone();
+    let members = decode_members(
+        &mut decoded,
+        &merges,
+        &options.limits,
+        rules.numbers,
+        &mut truncated,
+    )?;
+    let mut redactions = Vec::new();
+    if !options.redaction.is_empty() || options.detectors.any_enabled() {
+        // Redact the decoded view so rules and detectors reach inside decoded
+        // members, then re-encode so secrets do not survive in the protobuf
+        // Struct bytes either.
+        let before = decoded.clone();
+        apply_redaction_rules(&mut decoded, &options.redaction)?;
+        redactions = detect_and_redact(&mut decoded, &options.detectors);
+        if decoded != before {
+            normalized = encode_members(&before, &decoded, &members)?;
+        }
+    }
+    let schema_flags = SchemaFlags {
+        lossless_numbers,
+        truncated: &truncated,
+        union_items: rules.union_items,
+    };
+    let mut decoded_schema_value =
+        generate_schema_json_value(&decoded, &mut Vec::new(), schema_flags);
+    annotate_schema(&mut decoded_schema_value, &merges);
+    let hash = |v: &JsonValue| hash_json_value(v, rules.form, rules.algorithm);
+    let (decoded_value_hash, path_hashes) = if options.path_hash_depth > 0 {
+        hash_json_value_with_paths(
+            &decoded,
+            rules.form,
+            rules.algorithm,
+            options.path_hash_depth,
+        )?
     } else {
-        normalized.clone()
+        (hash(&decoded)?, BTreeMap::new())
     };
-    let merge_map = if let Some(merges_json) = schema_merges_json {
-        serde_json::from_str::<BTreeMap<String, MergeRule>>(merges_json)
-            .map_err(|e| CoreError::InvalidJson(e.to_string()))?
+    let match_hash = if options.volatile.any_enabled() {
+        let mut masked = decoded.clone();
+        mask_volatile(&mut masked, &options.volatile);
+        Some(hash(&masked)?)
     } else {
-        BTreeMap::new()
+        None
+    };
+    let importance_hash = match without_unimportant_fields(&decoded, &merges) {
+        Some(important) => hash(&important)?,
+        None => decoded_value_hash.clone(),
     };
-    let decoded_schema_value = generate_schema_json_value(&decoded, Some(&merge_map), true);
-    let decoded_value_hash = hash_json_value_deterministic(&decoded)?;
-    let decoded_schema_hash = hash_json_value_deterministic(&decoded_schema_value)?;
-    let protobuf_struct_bytes = json_object_to_struct(&normalized).encode_to_vec();
+    let fingerprint = options.fingerprint.then(|| simhash_fingerprint(&decoded));
+    let decoded_schema_hash = hash(&decoded_schema_value)?;
+    let shape_hash = hash(&shape_of(&decoded))?;
+    let protobuf_struct_bytes =
+        json_object_to_struct_with(&normalized, lossless_numbers).encode_to_vec();
 
     Ok(ExportPayloadValueResult {
         normalized_value: normalized,
         decoded_value: decoded,
         decoded_value_hash,
+        match_hash,
+        importance_hash,
+        fingerprint,
+        path_hashes,
         decoded_schema_value,
         decoded_schema_hash,
+        shape_hash,
+        hash_scheme: rules.scheme,
         protobuf_struct_bytes,
+        redactions,
     })
 }
 
-fn apply_schema_merges_top_level(
-    normalized: &JsonValue,
-    schema_merges_json: &str,
-) -> CoreResult<JsonValue> {
-    let merge_map: BTreeMap<String, MergeRule> = serde_json::from_str(schema_merges_json)
-        .map_err(|e| CoreError::InvalidJson(e.to_string()))?;
-
-    if merge_map.is_empty() {
-        return Ok(normalized.clone());
+/// Copy of `decoded` without the fields whose merge rule has
+/// `match_importance == 0`, or `None` when no rule does.
+fn without_unimportant_fields(decoded: &JsonValue, merges: &[SchemaMerge]) -> Option<JsonValue> {
+    let mut unimportant = merges
+        .iter()
+        .filter(|merge| merge.rule.match_importance == Some(0.0))
+        .peekable();
+    unimportant.peek()?;
+    let mut important = decoded.clone();
+    for merge in unimportant {
+        remove_matches(&mut important, &merge.segments);
     }
-
-    let mut decoded = match normalized {
-        JsonValue::Object(map) => map.clone(),
-        _ => return Ok(normalized.clone()),
-    };
-
-    for (key, merge) in &merge_map {
-        let Some(value) = decoded.get(key) else {
-            continue;
-        };
-        let mut working_value = value.clone();
-
-        if merge.encoding == Some(1)
-            && let JsonValue::String(s) = &working_value
-            && let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(s.as_bytes())
-        {
-            working_value = JsonValue::String(String::from_utf8_lossy(&bytes).to_string());
-        }
-
-        if merge.decoded_type == Some(1)
-            && let JsonValue::String(s) = &working_value
-            && let Ok(parsed) = serde_json::from_str::<JsonValue>(s)
-        {
-            working_value = parsed;
-        }
-
-        decoded.insert(key.clone(), working_value);
-    }
-
-    Ok(JsonValue::Object(decoded))
+    Some(important)
 }
 
-fn json_type_code(value: &JsonValue) -> i64 {
+pub(crate) fn json_type_code(value: &JsonValue) -> i64 {
     match value {
         JsonValue::Null => 4,      // NULL
         JsonValue::Bool(_) => 3,   // BOOLEAN
         JsonValue::Number(_) => 1, // NUMBER
         JsonValue::String(_) => 2, // STRING
         JsonValue::Array(_) => 7,  // ORDERED_LIST
+        JsonValue::Object(_) if lossless_literal(value).is_some() => 1,
         JsonValue::Object(_) => 6, // OBJECT
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct SchemaFlags<'a> {
+    lossless_numbers: bool,
+    /// Values shortened by `PayloadLimits`, flagged with `"truncated": true`.
+    truncated: &'a Truncations,
+    /// Merge every array element into `items` instead of describing only
+    /// the first one.
+    union_items: bool,
+}
+
+/// Schema of `value`, which sits at `path` in the decoded value.
 fn generate_schema_json_value(
     value: &JsonValue,
-    schema_merges: Option<&BTreeMap<String, MergeRule>>,
-    at_object_root: bool,
+    path: &mut Vec<PathSegment>,
+    flags: SchemaFlags,
 ) -> JsonValue {
     let mut schema_obj = serde_json::Map::new();
     schema_obj.insert(
@@ -118,41 +155,45 @@ fn generate_schema_json_value(
         "properties".to_string(),
         JsonValue::Object(serde_json::Map::new()),
     );
+    if flags.truncated.contains(path) {
+        schema_obj.insert("truncated".to_string(), JsonValue::Bool(true));
+    }
 
     match value {
+        _ if loses_digits_as_f64(value, flags.lossless_numbers) => {
+            // The protobuf Struct carries this number as its literal string.
+            schema_obj.insert("lossless_number".to_string(), JsonValue::Bool(true));
+        }
+        JsonValue::Array(arr) if flags.union_items => {
+            let items = arr
+                .iter()
+                .enumerate()
+                .map(|(i, child)| {
+                    path.push(PathSegment::Index(i));
+                    let schema = generate_schema_json_value(child, path, flags);
+                    path.pop();
+                    schema
+                })
+                .reduce(merge_schemas)
+                .unwrap_or_else(unknown_schema);
+            schema_obj.insert("items".to_string(), items);
+        }
         JsonValue::Array(arr) => {
             if let Some(first) = arr.first() {
+                path.push(PathSegment::Index(0));
                 schema_obj.insert(
                     "items".to_string(),
-                    generate_schema_json_value(first, None, false),
+                    generate_schema_json_value(first, path, flags),
                 );
+                path.pop();
             }
         }
         JsonValue::Object(map) => {
             let mut props = serde_json::Map::new();
             for (k, child) in map {
-                let mut child_schema = generate_schema_json_value(child, None, false);
-                if at_object_root
-                    && let Some(merges) = schema_merges
-                    && let Some(merge) = merges.get(k)
-                    && let JsonValue::Object(child_obj) = &mut child_schema
-                {
-                    if let Some(enc) = merge.encoding {
-                        child_obj.insert("encoding".to_string(), JsonValue::Number(enc.into()));
-                    }
-                    if let Some(decoded_type) = merge.decoded_type {
-                        child_obj.insert(
-                            "decoded_type".to_string(),
-                            JsonValue::Number(decoded_type.into()),
-                        );
-                    }
-                    if let Some(match_importance) = merge.match_importance
-                        && let Some(n) = serde_json::Number::from_f64(match_importance)
-                    {
-                        child_obj.insert("match_importance".to_string(), JsonValue::Number(n));
-                    }
-                }
-                props.insert(k.clone(), child_schema);
+                path.push(PathSegment::Key(k.clone()));
+                props.insert(k.clone(), generate_schema_json_value(child, path, flags));
+                path.pop();
             }
             schema_obj.insert("properties".to_string(), JsonValue::Object(props));
         }
@@ -161,3 +202,367 @@ fn generate_schema_json_value(
 
     JsonValue::Object(schema_obj)
 }
+
+/// Item schema of an empty array: no type seen yet.
+fn unknown_schema() -> JsonValue {
+    serde_json::json!({"type": 0, "properties": {}})
+}
+
+/// Type codes a schema node stands for: `types` when it is a union, else its
+/// `type`, plus NULL when `nullable`. UNSPECIFIED stands for nothing.
+pub(crate) fn schema_kinds(node: &serde_json::Map<String, JsonValue>) -> BTreeSet<i64> {
+    let mut kinds: BTreeSet<i64> = match node.get("types") {
+        Some(JsonValue::Array(types)) => types.iter().filter_map(JsonValue::as_i64).collect(),
+        _ => node
+            .get("type")
+            .and_then(JsonValue::as_i64)
+            .into_iter()
+            .collect(),
+    };
+    kinds.remove(&0);
+    if node.get("nullable") == Some(&JsonValue::Bool(true)) {
+        kinds.insert(4);
+    }
+    kinds
+}
+
+/// Union of two schema nodes, used for `union_items` arrays so every element
+/// counts, not just the first, and to accumulate samples. The result does not
+/// depend on the order nodes are merged in:
+///
+/// - one non-null type keeps its `type`; several become `"type": 0`
+///   (UNSPECIFIED) with their sorted codes in `"types"`; NULL next to other
+///   types becomes `"nullable": true`
+/// - `properties` and `items` merge recursively; a property missing from one
+///   of two object schemas gets `"optional": true`
+/// - `truncated`, `lossless_number` and `optional` are kept if either side
+///   has them
+/// - a merge annotation (`encoding`, `decoded_type`, `match_importance`) on
+///   one side is kept; when both sides disagree the smaller value wins
+///
+/// `types`, `nullable` and `optional` have no field in the `JsonSchema`
+/// protobuf, so span protos built from a merged node lose them.
+pub(crate) fn merge_schemas(a: JsonValue, b: JsonValue) -> JsonValue {
+    let (JsonValue::Object(mut a), JsonValue::Object(mut b)) = (a, b) else {
+        return unknown_schema();
+    };
+    let a_kinds = schema_kinds(&a);
+    let b_kinds = schema_kinds(&b);
+    let both_objects = a_kinds.contains(&6) && b_kinds.contains(&6);
+    let kinds: BTreeSet<i64> = a_kinds.union(&b_kinds).copied().collect();
+    let non_null: Vec<i64> = kinds.iter().copied().filter(|&k| k != 4).collect();
+
+    let mut out = serde_json::Map::new();
+    let type_code = match non_null[..] {
+        [only] => only,
+        [] if kinds.contains(&4) => 4,
+        _ => 0,
+    };
+    out.insert("type".to_string(), type_code.into());
+    if non_null.len() > 1 {
+        out.insert("types".to_string(), non_null.clone().into());
+    }
+    if kinds.contains(&4) && !non_null.is_empty() {
+        out.insert("nullable".to_string(), JsonValue::Bool(true));
+    }
+
+    let mut a_props = take_properties(&mut a);
+    let mut b_props = take_properties(&mut b);
+    let mut properties = serde_json::Map::new();
+    let keys: BTreeSet<String> = a_props.keys().chain(b_props.keys()).cloned().collect();
+    for key in keys {
+        let property = match (a_props.remove(&key), b_props.remove(&key)) {
+            (Some(x), Some(y)) => merge_schemas(x, y),
+            (Some(mut only), None) | (None, Some(mut only)) => {
+                if both_objects && let JsonValue::Object(only) = &mut only {
+                    only.insert("optional".to_string(), JsonValue::Bool(true));
+                }
+                only
+            }
+            (None, None) => continue,
+        };
+        properties.insert(key, property);
+    }
+    out.insert("properties".to_string(), JsonValue::Object(properties));
+
+    match (a.remove("items"), b.remove("items")) {
+        (Some(x), Some(y)) => {
+            out.insert("items".to_string(), merge_schemas(x, y));
+        }
+        (Some(only), None) | (None, Some(only)) => {
+            out.insert("items".to_string(), only);
+        }
+        (None, None) => {}
+    }
+    for flag in ["truncated", "lossless_number", "optional"] {
+        if a.contains_key(flag) || b.contains_key(flag) {
+            out.insert(flag.to_string(), JsonValue::Bool(true));
+        }
+    }
+    for annotation in ["encoding", "decoded_type", "match_importance"] {
+        let value = match (a.remove(annotation), b.remove(annotation)) {
+            (Some(x), Some(y)) => {
+                if (y.as_f64(), y.to_string()) < (x.as_f64(), x.to_string()) {
+                    y
+                } else {
+                    x
+                }
+            }
+            (Some(only), None) | (None, Some(only)) => only,
+            (None, None) => continue,
+        };
+        out.insert(annotation.to_string(), value);
+    }
+    JsonValue::Object(out)
+}
+
+fn take_properties(
+    node: &mut serde_json::Map<String, JsonValue>,
+) -> serde_json::Map<String, JsonValue> {
+    match node.remove("properties") {
+        Some(JsonValue::Object(properties)) => properties,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// Adds each merge rule's `encoding`, `decoded_type` and `match_importance`
+/// to the schema nodes its path reaches. Array indexes and wildcards over
+/// arrays land on the shared `items` schema.
+fn annotate_schema(schema: &mut JsonValue, merges: &[SchemaMerge]) {
+    for merge in merges {
+        for_each_schema_node_mut(schema, &merge.segments, &mut |node| {
+            let JsonValue::Object(node) = node else {
+                return;
+            };
+            if let Some(enc) = merge.rule.encoding {
+                node.insert("encoding".to_string(), JsonValue::Number(enc.into()));
+            }
+            if let Some(decoded_type) = merge.rule.decoded_type {
+                node.insert(
+                    "decoded_type".to_string(),
+                    JsonValue::Number(decoded_type.into()),
+                );
+            }
+            if let Some(match_importance) = merge.rule.match_importance
+                && let Some(n) = serde_json::Number::from_f64(match_importance)
+            {
+                node.insert("match_importance".to_string(), JsonValue::Number(n));
+            }
+        });
+    }
+}
+
+fn for_each_schema_node_mut(
+    schema: &mut JsonValue,
+    segments: &[PathSegment],
+    f: &mut dyn FnMut(&mut JsonValue),
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        f(schema);
+        return;
+    };
+    let JsonValue::Object(node) = schema else {
+        return;
+    };
+    if let Some(JsonValue::Object(props)) = node.get_mut("properties") {
+        match segment {
+            PathSegment::Key(k) => {
+                if let Some(child) = props.get_mut(k) {
+                    for_each_schema_node_mut(child, rest, f);
+                }
+            }
+            PathSegment::Wildcard => {
+                for child in props.values_mut() {
+                    for_each_schema_node_mut(child, rest, f);
+                }
+            }
+            PathSegment::Index(_) => {}
+        }
+    }
+    let element = match segment {
+        PathSegment::Key(k) => k.parse::<usize>().is_ok(),
+        PathSegment::Index(_) | PathSegment::Wildcard => true,
+    };
+    if element && let Some(items) = node.get_mut("items") {
+        for_each_schema_node_mut(items, rest, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::HashScheme;
+    use crate::payload::{process_export_payload, process_export_payload_with_options};
+    use crate::types::ExportPayloadOptions;
+    use serde_json::json;
+
+    fn schema_of(value: JsonValue) -> JsonValue {
+        generate_schema_json_value(
+            &value,
+            &mut Vec::new(),
+            SchemaFlags {
+                lossless_numbers: false,
+                truncated: &Truncations::default(),
+                union_items: true,
+            },
+        )
+    }
+
+    #[test]
+    fn v1_items_describe_the_first_element_only() {
+        let v1 = |value: JsonValue| {
+            generate_schema_json_value(
+                &value,
+                &mut Vec::new(),
+                SchemaFlags {
+                    lossless_numbers: false,
+                    truncated: &Truncations::default(),
+                    union_items: false,
+                },
+            )
+        };
+        assert_eq!(
+            v1(json!([1, "a", null])),
+            json!({"type": 7, "properties": {}, "items": {"type": 1, "properties": {}}})
+        );
+        assert_eq!(v1(json!([])), json!({"type": 7, "properties": {}}));
+    }
+
+    #[test]
+    fn conflicting_annotations_merge_the_same_either_way() {
+        let a = json!({"type": 2, "properties": {}, "encoding": 1002, "match_importance": 0.5});
+        let b = json!({"type": 2, "properties": {}, "encoding": 1, "decoded_type": 1});
+
+        let merged = merge_schemas(a.clone(), b.clone());
+        assert_eq!(merged, merge_schemas(b, a));
+        assert_eq!(
+            merged,
+            json!({
+                "type": 2,
+                "properties": {},
+                "encoding": 1,
+                "decoded_type": 1,
+                "match_importance": 0.5
+            })
+        );
+    }
+
+    #[test]
+    fn array_items_merge_every_element_in_any_order() {
+        let items = schema_of(
+            json!([{"id": 1, "tag": "a"}, {"id": 2}, {"id": null, "extra": [1, "x"]}]),
+        )["items"]
+            .clone();
+        assert_eq!(
+            items,
+            json!({
+                "type": 6,
+                "properties": {
+                    "extra": {
+                        "type": 7,
+                        "properties": {},
+                        "items": {"type": 0, "types": [1, 2], "properties": {}},
+                        "optional": true
+                    },
+                    "id": {"type": 1, "nullable": true, "properties": {}},
+                    "tag": {"type": 2, "properties": {}, "optional": true}
+                }
+            })
+        );
+        assert_eq!(
+            schema_of(json!([{"id": null, "extra": ["x", 1]}, {"id": 2}, {"tag": "a", "id": 1}])),
+            schema_of(json!([{"id": 1, "tag": "a"}, {"id": 2}, {"id": null, "extra": [1, "x"]}]))
+        );
+    }
+
+    #[test]
+    fn union_items_is_its_own_option() {
+        let items = |options_json: &str| {
+            let options =
+                ExportPayloadOptions::from_json(options_json).expect("options should parse");
+            let result = process_export_payload_with_options(r#"{"a":[1,"x"]}"#, None, &options)
+                .expect("processing should work");
+            let schema: JsonValue =
+                serde_json::from_str(&result.decoded_schema_json).expect("schema should parse");
+            (
+                schema["properties"]["a"]["items"].clone(),
+                result.hash_scheme,
+            )
+        };
+        let number = json!({"type": 1, "properties": {}});
+        let union = json!({"type": 0, "types": [1, 2], "properties": {}});
+
+        assert_eq!(items(r#"{"hash_scheme":"v2"}"#).0, number);
+        assert_eq!(items(r#"{"union_items":true}"#), (union.clone(), None));
+        assert_eq!(
+            items(r#"{"hash_scheme":"v2","union_items":true}"#),
+            (union, Some(HashScheme::V2))
+        );
+    }
+
+    #[test]
+    fn homogeneous_and_empty_arrays() {
+        assert_eq!(
+            schema_of(json!([1, 2, 3])),
+            json!({"type": 7, "properties": {}, "items": {"type": 1, "properties": {}}})
+        );
+        assert_eq!(
+            schema_of(json!([])),
+            json!({"type": 7, "properties": {}, "items": {"type": 0, "properties": {}}})
+        );
+        assert_eq!(
+            schema_of(json!([[], [true], null]))["items"],
+            json!({
+                "type": 7,
+                "nullable": true,
+                "properties": {},
+                "items": {"type": 3, "properties": {}}
+            })
+        );
+    }
+
+    #[test]
+    fn lossless_numbers_keep_big_ids_and_mark_them() {
+        let options = ExportPayloadOptions::from_json(r#"{"normalize":{"lossless_numbers":true}}"#)
+            .expect("options should parse");
+        let payload = r#"{"id":12345678901234567890123,"n":1}"#;
+        let lossless = process_export_payload_with_options(payload, None, &options)
+            .expect("processing should work");
+        let legacy = process_export_payload(payload, None).expect("processing should work");
+
+        assert_eq!(lossless.normalized_json, payload);
+        assert_ne!(lossless.decoded_value_hash, legacy.decoded_value_hash);
+        let schema: JsonValue =
+            serde_json::from_str(&lossless.decoded_schema_json).expect("schema should parse");
+        assert_eq!(
+            schema["properties"]["id"]["lossless_number"],
+            serde_json::json!(true)
+        );
+        assert!(schema["properties"]["n"].get("lossless_number").is_none());
+    }
+
+    #[test]
+    fn importance_hash_skips_zero_importance_fields() {
+        let merges = r#"{
+            "trace_id":{"match_importance":0},
+            "body.meta.at":{"match_importance":0},
+            "q":{"match_importance":0.5}
+        }"#;
+        let first = process_export_payload(
+            r#"{"trace_id":"a","body":{"meta":{"at":1,"v":2}},"q":"x"}"#,
+            Some(merges),
+        )
+        .expect("processing should work");
+        let replay = process_export_payload(
+            r#"{"trace_id":"b","body":{"meta":{"at":9,"v":2}},"q":"x"}"#,
+            Some(merges),
+        )
+        .expect("processing should work");
+
+        assert_ne!(first.decoded_value_hash, replay.decoded_value_hash);
+        assert_eq!(first.importance_hash, replay.importance_hash);
+
+        let plain = process_export_payload(r#"{"q":"x"}"#, None).expect("processing should work");
+        assert_eq!(plain.importance_hash, plain.decoded_value_hash);
+    }
+}
@@ -0,0 +1,288 @@
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::BTreeSet;
+
+use crate::error::CoreResult;
+use crate::normalize::parse_json;
+use crate::path::push_key;
+use crate::schema::schema_kinds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaChangeKind {
+    Added,
+    Removed,
+    TypeChanged,
+    OptionalityChanged,
+    EncodingChanged,
+    DecodedTypeChanged,
+    MatchImportanceChanged,
+    ItemsChanged,
+}
+
+impl SchemaChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SchemaChangeKind::Added => "added",
+            SchemaChangeKind::Removed => "removed",
+            SchemaChangeKind::TypeChanged => "type_changed",
+            SchemaChangeKind::OptionalityChanged => "optionality_changed",
+            SchemaChangeKind::EncodingChanged => "encoding_changed",
+            SchemaChangeKind::DecodedTypeChanged => "decoded_type_changed",
+            SchemaChangeKind::MatchImportanceChanged => "match_importance_changed",
+            SchemaChangeKind::ItemsChanged => "items_changed",
+        }
+    }
+}
+
+/// One difference between two schemas at a rendered path (`""` is the root,
+/// `[*]` an array's items). `before`/`after` hold the schema node for
+/// added/removed properties and items, the sorted type codes for type
+/// changes, and the keyword value otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaChange {
+    pub path: String,
+    pub kind: SchemaChangeKind,
+    pub breaking: bool,
+    pub before: Option<JsonValue>,
+    pub after: Option<JsonValue>,
+}
+
+/// Compares two Drift schemas in their JSON form, old first. A change is
+/// breaking when a value valid under the old schema may be rejected or read
+/// differently under the new one: removed or added required properties,
+/// optional properties that became required, narrowed or replaced types, and
+/// any change of `encoding` or `decoded_type`. Added optional properties,
+/// required properties that became optional, widened types and
+/// `match_importance` changes are not. Nodes with an unknown type (`type` 0
+/// without `types`) accept anything, so giving one a concrete type narrows
+/// it and dropping a concrete type widens it.
+pub fn diff_schemas(old_schema_json: &str, new_schema_json: &str) -> CoreResult<Vec<SchemaChange>> {
+    let old = parse_json(old_schema_json)?;
+    let new = parse_json(new_schema_json)?;
+    let mut changes = Vec::new();
+    if let (JsonValue::Object(old), JsonValue::Object(new)) = (&old, &new) {
+        diff_nodes(old, new, &mut String::new(), &mut changes);
+    }
+    Ok(changes)
+}
+
+fn diff_nodes(
+    old: &Map<String, JsonValue>,
+    new: &Map<String, JsonValue>,
+    path: &mut String,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let mut change = |kind, breaking, before: Option<JsonValue>, after: Option<JsonValue>| {
+        changes.push(SchemaChange {
+            path: path.clone(),
+            kind,
+            breaking,
+            before,
+            after,
+        })
+    };
+
+    let old_kinds = schema_kinds(old);
+    let new_kinds = schema_kinds(new);
+    if old_kinds != new_kinds {
+        // An empty set is the unknown type, which accepts anything.
+        let breaking =
+            !new_kinds.is_empty() && (old_kinds.is_empty() || !old_kinds.is_subset(&new_kinds));
+        change(
+            SchemaChangeKind::TypeChanged,
+            breaking,
+            Some(kinds_json(&old_kinds)),
+            Some(kinds_json(&new_kinds)),
+        );
+    }
+    let was_optional = is_optional(old);
+    if was_optional != is_optional(new) {
+        change(
+            SchemaChangeKind::OptionalityChanged,
+            was_optional,
+            Some(was_optional.into()),
+            Some((!was_optional).into()),
+        );
+    }
+    for (keyword, kind, breaking) in [
+        ("encoding", SchemaChangeKind::EncodingChanged, true),
+        ("decoded_type", SchemaChangeKind::DecodedTypeChanged, true),
+        (
+            "match_importance",
+            SchemaChangeKind::MatchImportanceChanged,
+            false,
+        ),
+    ] {
+        if old.get(keyword) != new.get(keyword) {
+            change(
+                kind,
+                breaking,
+                old.get(keyword).cloned(),
+                new.get(keyword).cloned(),
+            );
+        }
+    }
+
+    let empty = Map::new();
+    let old_props = properties(old).unwrap_or(&empty);
+    let new_props = properties(new).unwrap_or(&empty);
+    let names: BTreeSet<&String> = old_props.keys().chain(new_props.keys()).collect();
+    for name in names {
+        let len = path.len();
+        push_key(path, name);
+        match (old_props.get(name), new_props.get(name)) {
+            (Some(JsonValue::Object(before)), Some(JsonValue::Object(after))) => {
+                diff_nodes(before, after, path, changes);
+            }
+            (Some(before), None) => changes.push(SchemaChange {
+                path: path.clone(),
+                kind: SchemaChangeKind::Removed,
+                breaking: !before.as_object().is_some_and(is_optional),
+                before: Some(before.clone()),
+                after: None,
+            }),
+            (None, Some(after)) => changes.push(SchemaChange {
+                path: path.clone(),
+                kind: SchemaChangeKind::Added,
+                breaking: !after.as_object().is_some_and(is_optional),
+                before: None,
+                after: Some(after.clone()),
+            }),
+            _ => {}
+        }
+        path.truncate(len);
+    }
+
+    let len = path.len();
+    path.push_str("[*]");
+    match (old.get("items"), new.get("items")) {
+        (Some(JsonValue::Object(before)), Some(JsonValue::Object(after))) => {
+            diff_nodes(before, after, path, changes);
+        }
+        (None, None) => {}
+        (before, after) => {
+            // Items without a known type add or lose nothing.
+            let known = |items: Option<&JsonValue>| {
+                items
+                    .and_then(JsonValue::as_object)
+                    .is_some_and(|node| !schema_kinds(node).is_empty())
+            };
+            changes.push(SchemaChange {
+                path: path.clone(),
+                kind: SchemaChangeKind::ItemsChanged,
+                breaking: known(before) && !known(after),
+                before: before.cloned(),
+                after: after.cloned(),
+            });
+        }
+    }
+    path.truncate(len);
+}
+
+fn properties(node: &Map<String, JsonValue>) -> Option<&Map<String, JsonValue>> {
+    node.get("properties").and_then(JsonValue::as_object)
+}
+
+fn is_optional(node: &Map<String, JsonValue>) -> bool {
+    node.get("optional") == Some(&JsonValue::Bool(true))
+}
+
+fn kinds_json(kinds: &BTreeSet<i64>) -> JsonValue {
+    kinds.iter().copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn diff(old: JsonValue, new: JsonValue) -> Vec<(String, SchemaChangeKind, bool)> {
+        diff_schemas(&old.to_string(), &new.to_string())
+            .expect("diff should succeed")
+            .into_iter()
+            .map(|c| (c.path, c.kind, c.breaking))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_property_changes() {
+        let old = json!({"type": 6, "properties": {
+            "id": {"type": 1, "properties": {}},
+            "name": {"type": 2, "properties": {}},
+            "note": {"type": 2, "properties": {}, "optional": true},
+            "tag": {"type": 2, "properties": {}, "optional": true},
+            "body": {"type": 2, "properties": {}, "encoding": 1},
+            "a.b": {"type": 2, "properties": {}}
+        }});
+        let new = json!({"type": 6, "properties": {
+            "id": {"type": 0, "types": [1, 2], "properties": {}},
+            "name": {"type": 1, "properties": {}, "optional": true},
+            "tag": {"type": 2, "properties": {}},
+            "body": {"type": 2, "properties": {}, "encoding": 1004},
+            "extra": {"type": 3, "properties": {}},
+            "hint": {"type": 2, "properties": {}, "optional": true}
+        }});
+
+        assert_eq!(
+            diff(old, new),
+            [
+                ("[\"a.b\"]".to_string(), SchemaChangeKind::Removed, true),
+                ("body".to_string(), SchemaChangeKind::EncodingChanged, true),
+                ("extra".to_string(), SchemaChangeKind::Added, true),
+                ("hint".to_string(), SchemaChangeKind::Added, false),
+                ("id".to_string(), SchemaChangeKind::TypeChanged, false),
+                ("name".to_string(), SchemaChangeKind::TypeChanged, true),
+                (
+                    "name".to_string(),
+                    SchemaChangeKind::OptionalityChanged,
+                    false
+                ),
+                ("note".to_string(), SchemaChangeKind::Removed, false),
+                (
+                    "tag".to_string(),
+                    SchemaChangeKind::OptionalityChanged,
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn walks_array_items_and_ignores_identical_schemas() {
+        let schema = json!({"type": 7, "properties": {}, "items": {"type": 6, "properties": {
+            "tags": {"type": 7, "properties": {}, "items": {"type": 0, "properties": {}}}
+        }}});
+        assert_eq!(diff(schema.clone(), schema.clone()), []);
+
+        let mut changed = schema.clone();
+        changed["items"]["properties"]["tags"]["items"] = json!({"type": 2, "properties": {}});
+        changed["items"]["properties"]["tags"]["nullable"] = json!(true);
+        assert_eq!(
+            diff(schema, changed),
+            [
+                ("[*].tags".to_string(), SchemaChangeKind::TypeChanged, false),
+                (
+                    "[*].tags[*]".to_string(),
+                    SchemaChangeKind::TypeChanged,
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_types_accept_anything() {
+        let unknown = json!({"type": 0, "properties": {}});
+        let number = json!({"type": 1, "properties": {}});
+
+        assert_eq!(
+            diff(unknown.clone(), number.clone()),
+            [(String::new(), SchemaChangeKind::TypeChanged, true)]
+        );
+        assert_eq!(
+            diff(number, unknown),
+            [(String::new(), SchemaChangeKind::TypeChanged, false)]
+        );
+    }
+}
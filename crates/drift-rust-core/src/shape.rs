@@ -0,0 +1,116 @@
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::numbers::lossless_literal;
+
+/// Structure of a value with values, array lengths and element order erased.
+/// Each level is the union of every shape seen there, so
+/// `[{"a":1},{"b":null}]` and `[{"b":2,"a":3}]` describe the same contract
+/// once both have been seen.
+#[derive(Debug, Default)]
+struct Shape {
+    kinds: BTreeSet<&'static str>,
+    properties: Option<BTreeMap<String, Shape>>,
+    items: Option<Box<Shape>>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => {
+                self.kinds.insert("null");
+            }
+            JsonValue::Bool(_) => {
+                self.kinds.insert("boolean");
+            }
+            JsonValue::Number(_) => {
+                self.kinds.insert("number");
+            }
+            _ if lossless_literal(value).is_some() => {
+                self.kinds.insert("number");
+            }
+            JsonValue::String(_) => {
+                self.kinds.insert("string");
+            }
+            JsonValue::Array(arr) => {
+                self.kinds.insert("array");
+                let items = self.items.get_or_insert_default();
+                arr.iter().for_each(|child| items.add(child));
+            }
+            JsonValue::Object(map) => {
+                self.kinds.insert("object");
+                let properties = self.properties.get_or_insert_default();
+                for (k, child) in map {
+                    properties.entry(k.clone()).or_default().add(child);
+                }
+            }
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut out = serde_json::Map::new();
+        out.insert(
+            "kinds".to_string(),
+            self.kinds.iter().map(|k| JsonValue::from(*k)).collect(),
+        );
+        if let Some(properties) = &self.properties {
+            out.insert(
+                "properties".to_string(),
+                properties
+                    .iter()
+                    .map(|(k, shape)| (k.clone(), shape.to_json()))
+                    .collect(),
+            );
+        }
+        if let Some(items) = &self.items {
+            out.insert("items".to_string(), items.to_json());
+        }
+        JsonValue::Object(out)
+    }
+}
+
+/// Canonical JSON description of the shape of `value`, the input of
+/// `shape_hash`.
+pub(crate) fn shape_of(value: &JsonValue) -> JsonValue {
+    let mut shape = Shape::default();
+    shape.add(value);
+    shape.to_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::process_export_payload;
+
+    #[test]
+    fn shape_ignores_values_lengths_and_element_order() {
+        let a = serde_json::json!({"items": [{"id": 1}, {"id": 2, "tag": null}], "ok": true});
+        let b = serde_json::json!({"ok": false, "items": [{"tag": null, "id": 9}]});
+        let c = serde_json::json!({"ok": false, "items": [{"id": "9"}]});
+
+        assert_eq!(shape_of(&a), shape_of(&b));
+        assert_ne!(shape_of(&a), shape_of(&c));
+        assert_eq!(
+            shape_of(&serde_json::json!([1, "x", [], null])),
+            serde_json::json!({
+                "kinds": ["array"],
+                "items": {"kinds": ["array", "null", "number", "string"], "items": {"kinds": []}}
+            })
+        );
+    }
+
+    #[test]
+    fn shape_hash_groups_by_structure() {
+        let merges = r#"{"body":{"match_importance":0.5}}"#;
+        let short = process_export_payload(r#"{"body":{"ids":[1]},"q":"a"}"#, None)
+            .expect("processing should work");
+        let long = process_export_payload(r#"{"q":"b","body":{"ids":[3,2,1]}}"#, Some(merges))
+            .expect("processing should work");
+        let other = process_export_payload(r#"{"body":{"ids":["1"]},"q":"a"}"#, None)
+            .expect("processing should work");
+
+        assert_ne!(short.decoded_schema_hash, long.decoded_schema_hash);
+        assert_eq!(short.shape_hash, long.shape_hash);
+        assert_ne!(short.shape_hash, other.shape_hash);
+    }
+}
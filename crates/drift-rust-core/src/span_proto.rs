@@ -75,7 +75,11 @@ pub fn build_span_proto_bytes(input: BuildSpanProtoInput<'_>) -> CoreResult<Vec<
     Ok(span.encode_to_vec())
 }
 
-fn json_schema_from_value(value: &JsonValue) -> JsonSchema {
+/// `JsonSchema` protobuf for a decoded schema. The proto only has fields for
+/// `type`, `properties`, `items` and the merge annotations, so union `types`,
+/// `nullable`, `optional`, `truncated` and `lossless_number` stay in the JSON
+/// schema (`decoded_schema_json`) and are dropped here.
+pub(crate) fn json_schema_from_value(value: &JsonValue) -> JsonSchema {
     let obj = value.as_object();
     let schema_type = obj
         .and_then(|o| o.get("type"))
@@ -1,14 +1,94 @@
+use serde::Deserialize;
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 use tusk_drift_schemas::tusk::drift::core::v1::{PackageType, SpanKind, StatusCode};
 
+use crate::detect::{DetectedRedaction, DetectorOptions};
+use crate::error::{CoreError, CoreResult};
+use crate::hash::{CanonicalForm, HashAlgorithm, HashRules, HashScheme};
+use crate::limits::PayloadLimits;
+use crate::normalize::NormalizeOptions;
+use crate::redact::RedactionRule;
+use crate::volatile::VolatileOptions;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportPayloadOptions {
+    /// Checked first, before the payload is parsed or normalized.
+    pub limits: PayloadLimits,
+    pub normalize: NormalizeOptions,
+    /// Applied to the normalized value (and inside decoded members) before
+    /// any hash, schema or Struct bytes are produced.
+    pub redaction: Vec<RedactionRule>,
+    /// Content-based secret detection over every string leaf, run after
+    /// `redaction` and after schema merges have decoded their members.
+    pub detectors: DetectorOptions,
+    /// When any volatile kind is enabled, `match_hash` is computed over the
+    /// decoded value with those leaves masked.
+    pub volatile: VolatileOptions,
+    /// Digest for `decoded_value_hash`, `decoded_schema_hash` and
+    /// `match_hash`.
+    pub hash_algorithm: HashAlgorithm,
+    /// Pins the rules every hash in the result follows (see [`HashScheme`]);
+    /// unset uses `normalize`, `hash_algorithm` and `union_items` as given.
+    /// `v1` only accepts their defaults; `v2` also canonicalizes the numbers
+    /// of the normalized value.
+    pub hash_scheme: Option<HashScheme>,
+    /// Describe arrays in `decoded_schema` by the union of every element's
+    /// schema (`types`, `nullable`, `optional`) instead of by the first
+    /// element alone.
+    pub union_items: bool,
+    /// How many levels below the root get their own entry in `path_hashes`;
+    /// `0` disables them, `1` hashes each top-level key.
+    pub path_hash_depth: usize,
+    /// Compute a SimHash `fingerprint` of the decoded value for ranking
+    /// near matches with `fingerprint_similarity`.
+    pub fingerprint: bool,
+}
+
+impl ExportPayloadOptions {
+    pub fn from_json(options_json: &str) -> CoreResult<Self> {
+        serde_json::from_str(options_json).map_err(|e| CoreError::InvalidJson(e.to_string()))
+    }
+
+    pub(crate) fn hash_rules(&self) -> CoreResult<HashRules<'_>> {
+        HashRules::new(
+            self.hash_scheme,
+            &self.normalize,
+            CanonicalForm::Sorted,
+            self.hash_algorithm,
+            self.union_items,
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExportPayloadResult {
     pub normalized_json: String,
     pub decoded_json: String,
     pub decoded_value_hash: String,
+    /// Hash of the decoded value with volatile leaves masked, for replay
+    /// matching; `None` unless volatile masking is enabled.
+    pub match_hash: Option<String>,
+    /// Hash of the decoded value without fields whose schema merge has
+    /// `match_importance` 0; equals `decoded_value_hash` when there are none.
+    pub importance_hash: String,
+    pub fingerprint: Option<String>,
+    /// Rendered JSON path to subtree hash of the decoded value, down to
+    /// `path_hash_depth` levels.
+    pub path_hashes: BTreeMap<String, String>,
     pub decoded_schema_json: String,
     pub decoded_schema_hash: String,
+    /// Hash of the decoded value's structure alone: no values, array lengths
+    /// or schema annotations, with arrays as the union of their elements.
+    pub shape_hash: String,
+    /// Scheme the hashes above follow: the one asked for, or `v1` when
+    /// default options were hashed without one. `None` for options no
+    /// scheme pins.
+    pub hash_scheme: Option<HashScheme>,
     pub protobuf_struct_bytes: Vec<u8>,
+    /// What the enabled detectors replaced, in the decoded value.
+    pub redactions: Vec<DetectedRedaction>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,9 +96,28 @@ pub struct ExportPayloadValueResult {
     pub normalized_value: JsonValue,
     pub decoded_value: JsonValue,
     pub decoded_value_hash: String,
+    /// Hash of the decoded value with volatile leaves masked, for replay
+    /// matching; `None` unless volatile masking is enabled.
+    pub match_hash: Option<String>,
+    /// Hash of the decoded value without fields whose schema merge has
+    /// `match_importance` 0; equals `decoded_value_hash` when there are none.
+    pub importance_hash: String,
+    pub fingerprint: Option<String>,
+    /// Rendered JSON path to subtree hash of the decoded value, down to
+    /// `path_hash_depth` levels.
+    pub path_hashes: BTreeMap<String, String>,
     pub decoded_schema_value: JsonValue,
     pub decoded_schema_hash: String,
+    /// Hash of the decoded value's structure alone: no values, array lengths
+    /// or schema annotations, with arrays as the union of their elements.
+    pub shape_hash: String,
+    /// Scheme the hashes above follow: the one asked for, or `v1` when
+    /// default options were hashed without one. `None` for options no
+    /// scheme pins.
+    pub hash_scheme: Option<HashScheme>,
     pub protobuf_struct_bytes: Vec<u8>,
+    /// What the enabled detectors replaced, in the decoded value.
+    pub redactions: Vec<DetectedRedaction>,
 }
 
 pub struct BuildSpanProtoInput<'a> {
@@ -0,0 +1,286 @@
+use prost::Message;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use tusk_drift_schemas::tusk::drift::core::v1::JsonSchema;
+
+use crate::encoding::MAX_DECOMPRESSED_BYTES;
+use crate::error::{CoreError, CoreResult};
+use crate::json_schema::value_from_json_schema_proto;
+use crate::limits::PayloadLimits;
+use crate::merge::{MergeRule, decode_member_at};
+use crate::normalize::{NumberParsing, parse_json};
+use crate::path::{push_index, push_key};
+use crate::schema::{json_type_code, schema_kinds};
+
+/// UNDEFINED, reported as the actual type of a required property that is
+/// missing.
+const UNDEFINED: i32 = 5;
+
+/// A value whose type is not one its schema node allows. `path` is rendered
+/// like redaction paths (`""` is the root); `expected` lists the allowed type
+/// codes, sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub expected: Vec<i32>,
+    pub actual: i32,
+}
+
+/// Checks `value_json` against a Drift schema in its JSON form, the inverse
+/// of schema generation: values may differ freely, only types, missing
+/// required (non-`optional`) properties and array items are checked. Extra
+/// properties are allowed, nodes of unknown type (`type` 0) accept anything,
+/// and a string under a node with `encoding`/`decoded_type` is decoded first,
+/// so both raw and decoded payloads validate. A decoded member that would
+/// nest too deep fails with [`CoreError::LimitExceeded`].
+pub fn validate_against_schema(
+    value_json: &str,
+    schema_json: &str,
+) -> CoreResult<Vec<SchemaViolation>> {
+    let value = parse_json(value_json)?;
+    let schema = parse_json(schema_json)?;
+    let mut violations = Vec::new();
+    validate_node(&value, &schema, 0, &mut String::new(), &mut violations)?;
+    Ok(violations)
+}
+
+/// [`validate_against_schema`] with an encoded `JsonSchema` protobuf. The
+/// proto does not record optional properties, so every property is required.
+pub fn validate_against_schema_proto(
+    value_json: &str,
+    schema_proto_bytes: &[u8],
+) -> CoreResult<Vec<SchemaViolation>> {
+    let schema = JsonSchema::decode(schema_proto_bytes)
+        .map_err(|e| CoreError::SerializationError(e.to_string()))?;
+    let value = parse_json(value_json)?;
+    let mut violations = Vec::new();
+    validate_node(
+        &value,
+        &value_from_json_schema_proto(&schema),
+        0,
+        &mut String::new(),
+        &mut violations,
+    )?;
+    Ok(violations)
+}
+
+/// Checks `value`, which sits below `depth` enclosing containers.
+fn validate_node(
+    value: &JsonValue,
+    schema: &JsonValue,
+    depth: usize,
+    path: &mut String,
+    violations: &mut Vec<SchemaViolation>,
+) -> CoreResult<()> {
+    let Some(node) = schema.as_object() else {
+        return Ok(());
+    };
+    let decoded = decode_if_encoded(value, node, depth)?;
+    let value = decoded.as_ref().unwrap_or(value);
+
+    let kinds = expected_kinds(node);
+    let actual = json_type_code(value) as i32;
+    if !kinds.is_empty() && !kinds.contains(&actual) {
+        violations.push(SchemaViolation {
+            path: path.clone(),
+            expected: kinds,
+            actual,
+        });
+        return Ok(());
+    }
+
+    match value {
+        JsonValue::Object(map) => {
+            let Some(JsonValue::Object(properties)) = node.get("properties") else {
+                return Ok(());
+            };
+            for (name, property) in properties {
+                let len = path.len();
+                push_key(path, name);
+                match map.get(name) {
+                    Some(child) => validate_node(child, property, depth + 1, path, violations)?,
+                    None if !is_optional(property) => violations.push(SchemaViolation {
+                        path: path.clone(),
+                        expected: property.as_object().map(expected_kinds).unwrap_or_default(),
+                        actual: UNDEFINED,
+                    }),
+                    None => {}
+                }
+                path.truncate(len);
+            }
+        }
+        JsonValue::Array(items) => {
+            let Some(item_schema) = node.get("items") else {
+                return Ok(());
+            };
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                push_index(path, i);
+                validate_node(item, item_schema, depth + 1, path, violations)?;
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The type codes `node` allows, sorted, as [`json_type_code`] reports them:
+/// UNORDERED_LIST holds arrays too, so it reads as ORDERED_LIST.
+fn expected_kinds(node: &Map<String, JsonValue>) -> Vec<i32> {
+    let mut kinds: Vec<i32> = schema_kinds(node)
+        .into_iter()
+        .map(|kind| if kind == 8 { 7 } else { kind as i32 })
+        .collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+    kinds
+}
+
+/// The decoded form of a raw string member below `depth` enclosing
+/// containers, when the node carries merge annotations and does not itself
+/// accept strings. Decoding is bounded like the export pipeline's.
+fn decode_if_encoded(
+    value: &JsonValue,
+    node: &Map<String, JsonValue>,
+    depth: usize,
+) -> CoreResult<Option<JsonValue>> {
+    let rule = MergeRule {
+        encoding: node
+            .get("encoding")
+            .and_then(JsonValue::as_i64)
+            .map(|v| v as i32),
+        decoded_type: node
+            .get("decoded_type")
+            .and_then(JsonValue::as_i64)
+            .map(|v| v as i32),
+        match_importance: None,
+    };
+    if !value.is_string()
+        || (rule.encoding.is_none() && rule.decoded_type.is_none())
+        || schema_kinds(node).contains(&2)
+    {
+        return Ok(None);
+    }
+    // Decoding only feeds type checks, so compressed members are never
+    // inflated past the default cap even though validation takes no limits.
+    let limits = PayloadLimits {
+        max_bytes: Some(MAX_DECOMPRESSED_BYTES),
+        ..PayloadLimits::default()
+    };
+    Ok(
+        decode_member_at(value, depth, &rule, &limits, NumberParsing::Baseline)?
+            .map(|(decoded, _, _)| decoded),
+    )
+}
+
+fn is_optional(schema: &JsonValue) -> bool {
+    schema.get("optional") == Some(&JsonValue::Bool(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_schema::json_schema_to_drift_schema_proto;
+    use serde_json::json;
+
+    fn violations(value: JsonValue, schema: JsonValue) -> Vec<(String, Vec<i32>, i32)> {
+        validate_against_schema(&value.to_string(), &schema.to_string())
+            .expect("validation should succeed")
+            .into_iter()
+            .map(|v| (v.path, v.expected, v.actual))
+            .collect()
+    }
+
+    #[test]
+    fn reports_type_mismatches_and_missing_properties_with_paths() {
+        let schema = json!({
+            "type": 6,
+            "properties": {
+                "id": {"type": 1, "properties": {}},
+                "note": {"type": 2, "nullable": true, "properties": {}, "optional": true},
+                "items": {"type": 7, "properties": {}, "items": {"type": 6, "properties": {
+                    "sku": {"type": 2, "properties": {}}
+                }}},
+                "meta": {"type": 0, "properties": {}},
+                "set": {"type": 8, "properties": {}}
+            }
+        });
+
+        assert_eq!(
+            violations(
+                json!({"id": 99, "note": null, "items": [{"sku": "b", "qty": 2}], "meta": [1], "set": []}),
+                schema.clone()
+            ),
+            []
+        );
+        assert_eq!(
+            violations(
+                json!({"note": 3, "items": [{"sku": "a"}, {"sku": 1}, {}], "extra": true}),
+                schema
+            ),
+            [
+                ("id".to_string(), vec![1], UNDEFINED),
+                ("items[1].sku".to_string(), vec![2], 1),
+                ("items[2].sku".to_string(), vec![2], UNDEFINED),
+                ("meta".to_string(), vec![], UNDEFINED),
+                ("note".to_string(), vec![2, 4], 1),
+                ("set".to_string(), vec![7], UNDEFINED),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_raw_members_and_accepts_proto_schemas() {
+        let schema = json!({
+            "type": 6,
+            "properties": {
+                "body": {"type": 6, "encoding": 1, "decoded_type": 1, "properties": {
+                    "ok": {"type": 3, "properties": {}}
+                }}
+            }
+        });
+        assert_eq!(
+            violations(json!({"body": "eyJvayI6dHJ1ZX0="}), schema.clone()),
+            []
+        );
+        assert_eq!(
+            violations(json!({"body": "eyJvayI6MX0="}), schema),
+            [("body.ok".to_string(), vec![3], 1)]
+        );
+
+        let proto = json_schema_to_drift_schema_proto(
+            r#"{"type":"object","properties":{"n":{"type":"number"}}}"#,
+        )
+        .expect("proto should build");
+        let found = validate_against_schema_proto(r#"{"n":"1"}"#, &proto)
+            .expect("validation should succeed");
+        assert_eq!(
+            found,
+            [SchemaViolation {
+                path: "n".to_string(),
+                expected: vec![1],
+                actual: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn deeply_nested_members_are_bounded() {
+        let xml = |levels: usize| format!("{}1{}", "<a>".repeat(levels), "</a>".repeat(levels));
+        let schema = json!({"type": 6, "properties": {"x": {"type": 6, "properties": {
+            "body": {"type": 6, "decoded_type": 5, "properties": {}}
+        }}}});
+
+        let too_deep_here = json!({"x": {"body": xml(126)}});
+        assert!(matches!(
+            validate_against_schema(&too_deep_here.to_string(), &schema.to_string()),
+            Err(CoreError::LimitExceeded(_))
+        ));
+        // Too deep to decode at all, so checked as the raw string.
+        assert_eq!(
+            violations(json!({"x": {"body": xml(100_000)}}), schema),
+            [("x.body".to_string(), vec![6], 2)]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::sync::LazyLock;
+
+use crate::error::CoreError;
+use crate::numbers::lossless_literal;
+
+/// Leaves that change on every run (timestamps, ids, nonces). Matching leaves
+/// are replaced with `<VOLATILE:KIND>` before computing the match hash; the
+/// exported value and `decoded_value_hash` are left untouched.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct VolatileOptions {
+    /// ISO-8601 date-times.
+    pub timestamps: bool,
+    /// Integer epoch seconds/milliseconds, masked as `TIMESTAMP`. Separate
+    /// from `timestamps` since ids, counts and amounts fall in the same
+    /// ranges.
+    pub epoch_numbers: bool,
+    pub uuids: bool,
+    pub ulids: bool,
+    /// Hex strings of 16+ characters mixing digits and letters.
+    pub hex_nonces: bool,
+    /// String leaves matching any of these regexes (anywhere, unless
+    /// anchored) are masked as `<VOLATILE:PATTERN>`.
+    pub patterns: Vec<VolatilePattern>,
+}
+
+impl VolatileOptions {
+    pub(crate) fn any_enabled(&self) -> bool {
+        self.timestamps
+            || self.epoch_numbers
+            || self.uuids
+            || self.ulids
+            || self.hex_nonces
+            || !self.patterns.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct VolatilePattern(Regex);
+
+impl TryFrom<String> for VolatilePattern {
+    type Error = CoreError;
+
+    fn try_from(pattern: String) -> Result<Self, CoreError> {
+        Regex::new(&pattern)
+            .map(Self)
+            .map_err(|e| CoreError::InvalidJson(format!("invalid volatile pattern: {e}")))
+    }
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("volatile pattern is valid")
+}
+
+static ISO_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    regex(r"^\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:Z|[+-]\d{2}(?::?\d{2})?)?$")
+});
+static UUID: LazyLock<Regex> = LazyLock::new(|| {
+    regex(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$")
+});
+static ULID: LazyLock<Regex> = LazyLock::new(|| regex(r"^[0-7][0-9A-HJKMNP-TV-Z]{25}$"));
+static HEX_NONCE: LazyLock<Regex> = LazyLock::new(|| regex(r"^(?:0x)?[0-9a-fA-F]{16,}$"));
+
+/// Integer epoch seconds (2001-09-09 .. 2286-11-20) or milliseconds over the
+/// same range.
+fn is_epoch(n: &serde_json::Number) -> bool {
+    n.as_u64().is_some_and(|v| {
+        (1_000_000_000..10_000_000_000).contains(&v)
+            || (1_000_000_000_000..10_000_000_000_000).contains(&v)
+    })
+}
+
+fn volatile_kind(value: &JsonValue, options: &VolatileOptions) -> Option<&'static str> {
+    match value {
+        JsonValue::Number(n) if options.epoch_numbers && is_epoch(n) => Some("TIMESTAMP"),
+        JsonValue::String(s) => {
+            if options.timestamps && ISO_TIMESTAMP.is_match(s) {
+                Some("TIMESTAMP")
+            } else if options.uuids && UUID.is_match(s) {
+                Some("UUID")
+            } else if options.ulids && ULID.is_match(s) {
+                Some("ULID")
+            } else if options.hex_nonces
+                && HEX_NONCE.is_match(s)
+                && s.bytes().any(|b| b.is_ascii_digit())
+                && s.trim_start_matches("0x")
+                    .bytes()
+                    .any(|b| b.is_ascii_alphabetic())
+            {
+                Some("NONCE")
+            } else if options.patterns.iter().any(|p| p.0.is_match(s)) {
+                Some("PATTERN")
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Replaces every volatile leaf of `value` with its `<VOLATILE:KIND>` marker.
+pub(crate) fn mask_volatile(value: &mut JsonValue, options: &VolatileOptions) {
+    if lossless_literal(value).is_some() {
+        return;
+    }
+    if let Some(kind) = volatile_kind(value, options) {
+        *value = JsonValue::String(format!("<VOLATILE:{kind}>"));
+        return;
+    }
+    match value {
+        JsonValue::Array(arr) => arr.iter_mut().for_each(|v| mask_volatile(v, options)),
+        JsonValue::Object(map) => map.values_mut().for_each(|v| mask_volatile(v, options)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{process_export_payload, process_export_payload_with_options};
+    use crate::types::ExportPayloadOptions;
+
+    #[test]
+    fn masks_each_volatile_kind() {
+        let options: VolatileOptions = serde_json::from_str(
+            r#"{"timestamps":true,"epoch_numbers":true,"uuids":true,"ulids":true,"hex_nonces":true,"patterns":["^req_"]}"#,
+        )
+        .expect("options should parse");
+        let mut value = serde_json::json!({
+            "at": "2024-05-01T12:30:45.123Z",
+            "epoch_ms": 1714566645123u64,
+            "id": "3f2b8c1e-9d4a-4e6b-8f1a-2c3d4e5f6a7b",
+            "ulid": "01HWXYZ0ABCDEFGHJKMNPQRSTV",
+            "nonce": "9f86d081884c7d65",
+            "request": "req_abc",
+            "items": [{"created": "2024-05-01 12:30"}],
+            "kept": ["2024-05-01", 42, "hello", "deadbeefdeadbeef"]
+        });
+        mask_volatile(&mut value, &options);
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "at": "<VOLATILE:TIMESTAMP>",
+                "epoch_ms": "<VOLATILE:TIMESTAMP>",
+                "id": "<VOLATILE:UUID>",
+                "ulid": "<VOLATILE:ULID>",
+                "nonce": "<VOLATILE:NONCE>",
+                "request": "<VOLATILE:PATTERN>",
+                "items": [{"created": "<VOLATILE:TIMESTAMP>"}],
+                "kept": ["2024-05-01", 42, "hello", "deadbeefdeadbeef"]
+            })
+        );
+    }
+
+    #[test]
+    fn epoch_numbers_are_masked_only_when_asked_for() {
+        let mask = |options_json: &str| {
+            let options: VolatileOptions =
+                serde_json::from_str(options_json).expect("options should parse");
+            let mut value = serde_json::json!({"at": 1714566645, "user_id": 1234567890});
+            mask_volatile(&mut value, &options);
+            value
+        };
+
+        assert_eq!(
+            mask(r#"{"timestamps":true}"#),
+            serde_json::json!({"at": 1714566645, "user_id": 1234567890})
+        );
+        assert_eq!(
+            mask(r#"{"epoch_numbers":true}"#),
+            serde_json::json!({"at": "<VOLATILE:TIMESTAMP>", "user_id": "<VOLATILE:TIMESTAMP>"})
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let err = serde_json::from_str::<VolatileOptions>(r#"{"patterns":["("]}"#)
+            .expect_err("invalid regex should fail");
+        assert!(err.to_string().contains("invalid volatile pattern"));
+    }
+
+    #[test]
+    fn match_hash_ignores_volatile_leaves() {
+        let options =
+            ExportPayloadOptions::from_json(r#"{"volatile":{"timestamps":true,"uuids":true}}"#)
+                .expect("options should parse");
+        let first =
+            r#"{"id":"3f2b8c1e-9d4a-4e6b-8f1a-2c3d4e5f6a7b","at":"2024-05-01T12:30:45Z","q":"x"}"#;
+        let replay =
+            r#"{"id":"0b7e4a52-1c3d-4f5e-9a8b-7c6d5e4f3a2b","at":"2024-06-02T08:00:00Z","q":"x"}"#;
+        let first = process_export_payload_with_options(first, None, &options)
+            .expect("processing should work");
+        let replay = process_export_payload_with_options(replay, None, &options)
+            .expect("processing should work");
+
+        assert_ne!(first.decoded_value_hash, replay.decoded_value_hash);
+        assert!(first.match_hash.is_some());
+        assert_eq!(first.match_hash, replay.match_hash);
+        assert!(
+            process_export_payload(r#"{"q":"x"}"#, None)
+                .expect("processing should work")
+                .match_hash
+                .is_none()
+        );
+    }
+}
@@ -2,8 +2,9 @@ use std::fs;
 use std::path::PathBuf;
 
 use drift_rust_core::{
-    deterministic_hash, normalize_and_hash, normalize_json, object_to_protobuf_struct_bytes,
-    object_to_protobuf_struct_field_count,
+    ExportPayloadOptions, HashOptions, deterministic_hash, normalize_and_hash,
+    normalize_and_hash_with_options, normalize_json, object_to_protobuf_struct_bytes,
+    object_to_protobuf_struct_field_count, process_export_payload_with_options,
 };
 
 fn fixture_path(name: &str) -> PathBuf {
@@ -55,3 +56,39 @@ fn basic_fixture_smoke() {
         "top-level protobuf struct should contain fields"
     );
 }
+
+#[test]
+fn numbers_fixture_canonicalizes_across_runtimes() {
+    let raw = fs::read_to_string(fixture_path("numbers.json")).expect("fixture should be readable");
+    let fixture: serde_json::Value =
+        serde_json::from_str(&raw).expect("fixture should be valid json");
+    let options = HashOptions::from_json(&fixture["options"].to_string())
+        .expect("fixture options should parse");
+    let expected_normalized = fixture["expected_normalized_json"]
+        .as_str()
+        .expect("fixture must include expected_normalized_json");
+    let expected_hash = fixture["expected_hash"]
+        .as_str()
+        .expect("fixture must include expected_hash");
+
+    // `input` keeps float literals such as `1.0` (what Python's json.dumps
+    // emits) while `equivalent_json` is what JSON.stringify emits in Node.
+    for payload in [
+        fixture["input"].to_string(),
+        fixture["equivalent_json"]
+            .as_str()
+            .expect("fixture must include equivalent_json")
+            .to_string(),
+    ] {
+        let (normalized, hash) =
+            normalize_and_hash_with_options(&payload, &options).expect("hashing should succeed");
+        assert_eq!(normalized, expected_normalized);
+        assert_eq!(hash, expected_hash);
+
+        let export_options = ExportPayloadOptions::from_json(&fixture["options"].to_string())
+            .expect("fixture options should parse");
+        let result = process_export_payload_with_options(&payload, None, &export_options)
+            .expect("export processing should succeed");
+        assert_eq!(result.decoded_value_hash, expected_hash);
+    }
+}
@@ -51,4 +51,116 @@ if (!Number.isInteger(fieldCount) || fieldCount <= 0) {
   throw new Error("object_to_protobuf_struct_field_count did not return positive integer");
 }
 
+const numbersPath = path.join(repoRoot, "tests", "fixtures", "numbers.json");
+const numbers = JSON.parse(fs.readFileSync(numbersPath, "utf-8"));
+// JSON.parse drops the float/int distinction, so `1.0` is re-emitted as `1`.
+const numbersPayloadJson = JSON.stringify(numbers.input);
+const numbersOptionsJson = JSON.stringify(numbers.options);
+const numbersCombined = binding.normalizeAndHash(numbersPayloadJson, numbersOptionsJson);
+const numbersExport = binding.processExportPayload(numbersPayloadJson, null, numbersOptionsJson);
+
+if (numbersCombined.normalizedJson !== numbers.expected_normalized_json) {
+  throw new Error("canonical numbers normalized output did not match fixture");
+}
+if (numbersCombined.deterministicHash !== numbers.expected_hash) {
+  throw new Error("canonical numbers hash did not match fixture");
+}
+if (numbersExport.decodedValueHash !== numbers.expected_hash) {
+  throw new Error("canonical numbers export hash did not match fixture");
+}
+
+if (!binding.deterministicHash(payloadJson, JSON.stringify({ algorithm: "blake3" })).startsWith("b3:")) {
+  throw new Error("blake3 deterministic hash is missing its b3: prefix");
+}
+const xxh3Export = binding.processExportPayload(
+  payloadJson,
+  null,
+  JSON.stringify({ hash_algorithm: "xxh3" }),
+);
+if (!xxh3Export.decodedValueHash.startsWith("xxh3:") || xxh3Export.decodedValueHash.length !== 37) {
+  throw new Error("xxh3 export hash is not an xxh3:-prefixed 128-bit digest");
+}
+if (binding.defaultHashScheme() !== "v1" || !binding.hashSchemes().includes("v2")) {
+  throw new Error("hash schemes are not discoverable");
+}
+if (binding.processExportPayload(payloadJson, null, JSON.stringify({ hash_scheme: "v2" })).hashScheme !== "v2") {
+  throw new Error("export result does not report its hash scheme");
+}
+const pinnedV1 = binding.processExportPayload(payloadJson, null, JSON.stringify({ hash_scheme: "v1" }));
+if (pinnedV1.decodedValueHash !== binding.processExportPayload(payloadJson).decodedValueHash) {
+  throw new Error("v1 scheme does not pin the default hash");
+}
+if (binding.processExportPayload(payloadJson).hashScheme !== "v1") {
+  throw new Error("default export does not report the v1 scheme");
+}
+let rejectedV1 = false;
+try {
+  binding.processExportPayload(payloadJson, null, JSON.stringify({ hash_scheme: "v1", hash_algorithm: "xxh3" }));
+} catch (e) {
+  rejectedV1 = String(e.message).includes("invalid options");
+}
+if (!rejectedV1) {
+  throw new Error("v1 with a non-default algorithm should be rejected");
+}
+const batch = await binding.processExportPayloadBatch([payloadJson, "{bad"], null, null);
+if (batch[0].result?.decodedValueHash !== binding.processExportPayload(payloadJson).decodedValueHash || !batch[1].error) {
+  throw new Error("export batch should keep order and report per-item errors");
+}
+if ((await binding.normalizeAndHashBatch([payloadJson]))[0].result?.deterministicHash !== binding.deterministicHash(payloadJson)) {
+  throw new Error("normalize-and-hash batch differs from the single-item call");
+}
+await binding.normalizeAndHashBatch([payloadJson], "{bad").then(
+  () => {
+    throw new Error("a batch with bad options should reject");
+  },
+  () => {},
+);
+const fingerprinted = binding.processExportPayload(payloadJson, null, JSON.stringify({ fingerprint: true }));
+if (binding.fingerprintSimilarity(fingerprinted.fingerprint, fingerprinted.fingerprint) !== 1) {
+  throw new Error("a fingerprint should be fully similar to itself");
+}
+const firstState = binding.accumulatePayloadSchemas(['{"a":1}']);
+const resumed = JSON.parse(binding.accumulatePayloadSchemas(['{"a":null,"b":true}'], firstState));
+if (resumed.samples !== 2 || resumed.schema.properties.a.nullable !== true || resumed.schema.properties.b.optional !== true) {
+  throw new Error("accumulated schema should track nullable and optional properties");
+}
+if (JSON.parse(binding.mergeSchemaAccumulators([firstState, firstState])).samples !== 2) {
+  throw new Error("merged accumulators should add their samples");
+}
+const standardSchema = JSON.parse(binding.driftSchemaToJsonSchema(JSON.stringify({ type: 6, properties: { a: { type: 1, properties: {}, encoding: 1 } } })));
+if (standardSchema.type !== "object" || standardSchema.properties.a["x-drift-encoding"] !== 1) {
+  throw new Error("drift schema should export as JSON Schema with x-drift keywords");
+}
+if (JSON.parse(binding.driftSchemaProtoToJsonSchema(binding.jsonSchemaToDriftSchemaProto(JSON.stringify(standardSchema)))).required[0] !== "a") {
+  throw new Error("JSON Schema should round trip through the proto form");
+}
+const schemaChanges = binding.diffSchemas(
+  JSON.stringify({ type: 6, properties: { a: { type: 1, properties: {} } } }),
+  JSON.stringify({ type: 6, properties: { a: { type: 2, properties: {} } } }),
+);
+if (schemaChanges.length !== 1 || schemaChanges[0].path !== "a" || schemaChanges[0].kind !== "type_changed" || !schemaChanges[0].breaking) {
+  throw new Error("schema diff should report a breaking type change");
+}
+const violations = binding.validateAgainstSchema(
+  JSON.stringify({ a: "x" }),
+  JSON.stringify({ type: 6, properties: { a: { type: 1, properties: {} }, b: { type: 3, properties: {} } } }),
+);
+if (violations.length !== 2 || violations[0].path !== "a" || violations[0].actual !== 2 || violations[1].actual !== 5) {
+  throw new Error("validation should report a type mismatch and a missing property");
+}
+
+const detected = binding.processExportPayload(
+  JSON.stringify({ to: "ops@example.com" }),
+  null,
+  JSON.stringify({ detectors: { email: true } }),
+);
+if (
+  detected.normalizedJson !== '{"to":"<REDACTED:EMAIL>"}' ||
+  detected.redactions.length !== 1 ||
+  detected.redactions[0].path !== "to" ||
+  detected.redactions[0].detector !== "EMAIL"
+) {
+  throw new Error("email detector redaction did not match expected output");
+}
+
 console.log("OK: node binding smoke passed");
@@ -3,6 +3,7 @@ from __future__ import annotations
 
 import json
 import sys
+from decimal import Decimal
 from pathlib import Path
 
 
@@ -33,6 +34,137 @@ def main() -> int:
     assert isinstance(proto_bytes, (bytes, bytearray)) and len(proto_bytes) > 0
     assert isinstance(field_count, int) and field_count > 0
 
+    numbers_path = repo_root / "tests" / "fixtures" / "numbers.json"
+    numbers = json.loads(numbers_path.read_text(encoding="utf-8"))
+    # json.loads keeps `1.0` as a float, so json.dumps re-emits float literals
+    # that JSON.stringify would print as integers on the Node side.
+    numbers_payload_json = json.dumps(numbers["input"], separators=(",", ":"))
+    numbers_normalized, numbers_digest = binding.normalize_and_hash(
+        numbers_payload_json, json.dumps(numbers["options"])
+    )
+    _, numbers_value_hash, _, _, _ = binding.process_export_payload_pyobject(
+        numbers["input"], None, numbers["options"]
+    )
+
+    assert numbers_normalized == numbers["expected_normalized_json"]
+    assert numbers_digest == numbers["expected_hash"]
+    assert numbers_value_hash == numbers["expected_hash"]
+
+    assert binding.deterministic_hash(
+        payload_json, json.dumps({"algorithm": "blake3"})
+    ).startswith("b3:")
+    _, _, xxh3_value_hash, _, _, _ = binding.process_export_payload(
+        payload_json, None, json.dumps({"hash_algorithm": "xxh3"})
+    )
+    assert xxh3_value_hash.startswith("xxh3:") and len(xxh3_value_hash) == 37
+
+    detailed = binding.process_export_payload_detailed(
+        {"to": "ops@example.com"}, None, {"detectors": {"email": True}}
+    )
+    assert detailed["normalized_value"] == {"to": "<REDACTED:EMAIL>"}
+    assert detailed["redactions"] == [{"path": "to", "detector": "EMAIL"}]
+
+    assert binding.default_hash_scheme() == "v1"
+    assert binding.hash_schemes() == ["v1", "v2"]
+    assert (
+        binding.process_export_payload_detailed({"n": 1.0}, None, {"hash_scheme": "v2"})[
+            "hash_scheme"
+        ]
+        == "v2"
+    )
+    assert binding.process_export_payload(
+        payload_json, None, json.dumps({"hash_scheme": "v1"})
+    ) == binding.process_export_payload(payload_json)
+    assert binding.process_export_payload_detailed({"n": 1}, None)["hash_scheme"] == "v1"
+    try:
+        binding.process_export_payload(
+            payload_json, None, json.dumps({"hash_scheme": "v1", "hash_algorithm": "xxh3"})
+        )
+        raise AssertionError("v1 with a non-default algorithm should be rejected")
+    except ValueError as e:
+        assert "invalid options" in str(e)
+
+    # Wide ints and Decimals convert as floats (non-finite ones as None), with
+    # the hash the baseline binding produced, unless lossless numbers or hash
+    # scheme v2 are requested; those keep their digits.
+    wide = {
+        "big": 2**70,
+        "u": 2**63,
+        "price": Decimal("0.10000000000000000001"),
+        "nan": Decimal("NaN"),
+        "inf": Decimal("-Infinity"),
+    }
+    wide_value, wide_hash, _, _, _ = binding.process_export_payload_pyobject(wide)
+    assert wide_value == {
+        "big": 1.1805916207174112e21,
+        "u": 9.223372036854776e18,
+        "price": 0.1,
+        "nan": None,
+        "inf": None,
+    }
+    assert wide_hash == "6fff98599711ea8042575cfffabb49b4bf550db821fbc5609f8b2fdf59a67a73"
+    del wide["nan"], wide["inf"]
+    lossless_value, _, _, _, _ = binding.process_export_payload_pyobject(
+        wide, None, {"normalize": {"lossless_numbers": True}}
+    )
+    assert lossless_value == wide and isinstance(lossless_value["price"], Decimal)
+    v2_value, _, _, _, _ = binding.process_export_payload_pyobject(
+        wide, None, {"hash_scheme": "v2"}
+    )
+    assert v2_value["big"] == float(2**70) and v2_value["u"] == 2**63
+    try:
+        binding.process_export_payload_pyobject({"nan": Decimal("NaN")}, None, {"hash_scheme": "v2"})
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("exact numbers should reject a NaN Decimal")
+
+    batch = binding.process_export_payload_batch([payload_json, "{bad"])
+    assert batch[0] == (binding.process_export_payload(payload_json), None)
+    assert batch[1][0] is None and batch[1][1]
+    assert binding.normalize_and_hash_batch([payload_json]) == [
+        (binding.normalize_and_hash(payload_json), None)
+    ]
+
+    fingerprint = binding.process_export_payload_detailed(
+        {"to": "ops@example.com"}, None, {"fingerprint": True}
+    )["fingerprint"]
+    assert binding.fingerprint_similarity(fingerprint, fingerprint) == 1.0
+
+    first_state = binding.accumulate_payload_schemas(['{"a":1}'])
+    resumed = json.loads(
+        binding.accumulate_payload_schemas(['{"a":null,"b":true}'], first_state)
+    )
+    assert resumed["samples"] == 2
+    assert resumed["schema"]["properties"]["a"]["nullable"] is True
+    assert resumed["schema"]["properties"]["b"]["optional"] is True
+    merged = json.loads(binding.merge_schema_accumulators([first_state, first_state]))
+    assert merged["samples"] == 2
+
+    standard_schema = json.loads(
+        binding.drift_schema_to_json_schema(
+            json.dumps({"type": 6, "properties": {"a": {"type": 1, "properties": {}, "encoding": 1}}})
+        )
+    )
+    assert standard_schema["type"] == "object"
+    assert standard_schema["properties"]["a"]["x-drift-encoding"] == 1
+    proto = binding.json_schema_to_drift_schema_proto(json.dumps(standard_schema))
+    assert json.loads(binding.drift_schema_proto_to_json_schema(proto))["required"] == ["a"]
+
+    changes = binding.diff_schemas(
+        json.dumps({"type": 6, "properties": {"a": {"type": 1, "properties": {}}}}),
+        json.dumps({"type": 6, "properties": {"a": {"type": 2, "properties": {}}}}),
+    )
+    assert changes == [
+        {"path": "a", "kind": "type_changed", "breaking": True, "before": [1], "after": [2]}
+    ]
+
+    violations = binding.validate_against_schema(
+        json.dumps({"a": "x"}),
+        json.dumps({"type": 6, "properties": {"a": {"type": 1, "properties": {}}}}),
+    )
+    assert violations == [{"path": "a", "expected": [1], "actual": 2}]
+
     print("OK: python binding smoke passed")
     return 0
 